@@ -45,5 +45,54 @@ fn oneshot(c: &mut Criterion) {
     }
 }
 
-criterion_group!(digest, oneshot);
+fn tiny_updates(c: &mut Criterion) {
+    for &(alg_name, algorithm) in ALGORITHMS {
+        c.bench_function(&format!("digest::tiny_updates::{alg_name}"), |b| {
+            let input = [0u8; 1024];
+            b.iter(|| -> usize {
+                let mut ctx = digest::Context::new(algorithm);
+                // Each `update` starts at a block boundary and doesn't
+                // complete a block, except the last of each block.
+                for chunk in input.chunks(algorithm.block_len() / 2) {
+                    ctx.update(chunk);
+                }
+                black_box(ctx.finish().as_ref().len())
+            })
+        });
+    }
+}
+
+fn hex(c: &mut Criterion) {
+    let digests: Vec<digest::Digest> = (0u32..1000)
+        .map(|i| digest::digest(&digest::SHA256, &i.to_be_bytes()))
+        .collect();
+
+    c.bench_function("digest::hex::to_hex::sha256x1000", |b| {
+        b.iter(|| -> usize {
+            digests
+                .iter()
+                .map(|digest| black_box(digest.to_hex()).len())
+                .sum()
+        })
+    });
+
+    // The per-byte `format!` approach that `to_hex` replaces, for comparison.
+    c.bench_function("digest::hex::per_byte_format::sha256x1000", |b| {
+        b.iter(|| -> usize {
+            digests
+                .iter()
+                .map(|digest| {
+                    let hex: String = digest
+                        .as_ref()
+                        .iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect();
+                    black_box(hex).len()
+                })
+                .sum()
+        })
+    });
+}
+
+criterion_group!(digest, oneshot, tiny_updates, hex);
 criterion_main!(digest);
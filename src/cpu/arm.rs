@@ -127,6 +127,12 @@ features! {
         mask: 1 << 2,
     },
 
+    // Keep in sync with `ARMV8_SHA1`.
+    // "sha2" covers both SHA-1 and SHA-256.
+    "sha2" => SHA1 {
+        mask: 1 << 3,
+    },
+
     // Keep in sync with `ARMV8_SHA256`.
     "sha2" => SHA256 {
         mask: 1 << 4,
@@ -228,6 +234,7 @@ mod tests {
     #[test]
     fn test_mask_abi_hw() {
         assert_eq!(AES.mask, 4);
+        assert_eq!(SHA1.mask, 8);
         assert_eq!(SHA256.mask, 16);
         assert_eq!(PMULL.mask, 32);
         assert_eq!(SHA512.mask, 64);
@@ -12,7 +12,7 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use super::{AES, ARMCAP_STATIC, NEON, PMULL, SHA256, SHA512};
+use super::{AES, ARMCAP_STATIC, NEON, PMULL, SHA1, SHA256, SHA512};
 use crate::polyfill::cstr;
 
 // ```
@@ -32,7 +32,7 @@ use crate::polyfill::cstr;
 // the other targets yet, we wouldn't have a way of testing the dynamic detection if we statically
 // enabled `SHA512` for -darwin. So instead, temporarily, we statically ignore the static
 // availability of the feature on -darwin so that it runs the dynamic detection.
-pub const MIN_STATIC_FEATURES: u32 = NEON.mask | AES.mask | SHA256.mask | PMULL.mask;
+pub const MIN_STATIC_FEATURES: u32 = NEON.mask | AES.mask | SHA1.mask | SHA256.mask | PMULL.mask;
 pub const FORCE_DYNAMIC_DETECTION: u32 = !MIN_STATIC_FEATURES;
 
 // MSRV: Enforce 1.61.0 onaarch64-apple-*, in particular) prior to. Earlier
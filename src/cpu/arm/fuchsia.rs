@@ -12,7 +12,7 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use super::{AES, ARMCAP_STATIC, NEON, PMULL, SHA256};
+use super::{AES, ARMCAP_STATIC, NEON, PMULL, SHA1, SHA256};
 
 pub const FORCE_DYNAMIC_DETECTION: u32 = 0;
 
@@ -29,6 +29,7 @@ pub fn detect_features() -> u32 {
     const ZX_ARM64_FEATURE_ISA_ASIMD: u32 = 1 << 2;
     const ZX_ARM64_FEATURE_ISA_AES: u32 = 1 << 3;
     const ZX_ARM64_FEATURE_ISA_PMULL: u32 = 1 << 4;
+    const ZX_ARM64_FEATURE_ISA_SHA1: u32 = 1 << 5;
     const ZX_ARM64_FEATURE_ISA_SHA2: u32 = 1 << 6;
 
     let mut caps = 0;
@@ -46,6 +47,9 @@ pub fn detect_features() -> u32 {
         if caps & ZX_ARM64_FEATURE_ISA_PMULL == ZX_ARM64_FEATURE_ISA_PMULL {
             features |= PMULL.mask;
         }
+        if caps & ZX_ARM64_FEATURE_ISA_SHA1 == ZX_ARM64_FEATURE_ISA_SHA1 {
+            features |= SHA1.mask;
+        }
         if caps & ZX_ARM64_FEATURE_ISA_SHA2 == ZX_ARM64_FEATURE_ISA_SHA2 {
             features |= SHA256.mask;
         }
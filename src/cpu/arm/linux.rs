@@ -44,8 +44,8 @@ pub fn detect_features() -> u32 {
 
 #[cfg(all(not(target_env = "uclibc"), target_arch = "aarch64"))]
 pub fn detect_features() -> u32 {
-    use super::{AES, ARMCAP_STATIC, PMULL, SHA256, SHA512};
-    use libc::{getauxval, AT_HWCAP, HWCAP_AES, HWCAP_PMULL, HWCAP_SHA2, HWCAP_SHA512};
+    use super::{AES, ARMCAP_STATIC, PMULL, SHA1, SHA256, SHA512};
+    use libc::{getauxval, AT_HWCAP, HWCAP_AES, HWCAP_PMULL, HWCAP_SHA1, HWCAP_SHA2, HWCAP_SHA512};
 
     let mut features = 0;
 
@@ -60,6 +60,9 @@ pub fn detect_features() -> u32 {
     if caps & HWCAP_PMULL == HWCAP_PMULL {
         features |= PMULL.mask;
     }
+    if caps & HWCAP_SHA1 == HWCAP_SHA1 {
+        features |= SHA1.mask;
+    }
     if caps & HWCAP_SHA2 == HWCAP_SHA2 {
         features |= SHA256.mask;
     }
@@ -12,7 +12,7 @@
 // OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
 // CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
 
-use super::{AES, ARMCAP_STATIC, NEON, PMULL, SHA256};
+use super::{AES, ARMCAP_STATIC, NEON, PMULL, SHA1, SHA256};
 use windows_sys::Win32::System::Threading::{
     IsProcessorFeaturePresent, PF_ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE,
 };
@@ -31,6 +31,7 @@ pub fn detect_features() -> u32 {
         // These are all covered by one call in Windows
         features |= AES.mask;
         features |= PMULL.mask;
+        features |= SHA1.mask;
         features |= SHA256.mask;
     }
 
@@ -98,6 +98,12 @@ pub(crate) const SSE41: Feature = Feature {
     mask: 1 << 19,
 };
 
+#[cfg(target_arch = "x86_64")]
+pub(crate) const SSE42: Feature = Feature {
+    word: 1,
+    mask: 1 << 20,
+};
+
 #[cfg(target_arch = "x86_64")]
 pub(crate) const MOVBE: Feature = Feature {
     word: 1,
@@ -115,6 +121,12 @@ pub(crate) const AVX: Feature = Feature {
     mask: 1 << 28,
 };
 
+#[cfg(target_arch = "x86_64")]
+pub(crate) const AVX2: Feature = Feature {
+    word: 2,
+    mask: 1 << 5,
+};
+
 #[cfg(all(target_arch = "x86_64", test))]
 mod x86_64_tests {
     use super::*;
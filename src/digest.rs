@@ -17,6 +17,19 @@
 //! If all the data is available in a single contiguous slice then the `digest`
 //! function should be used. Otherwise, the digest can be calculated in
 //! multiple steps using `Context`.
+//!
+//! # Compatibility with upstream *ring*
+//!
+//! This crate is published under the name `ring`, so code written against
+//! upstream *ring* 0.17's `ring::digest` compiles against this module
+//! unchanged; no shim is needed. Everything in upstream's `ring::digest`
+//! has the same name and signature here, and everything else is an
+//! addition. The differences in behavior are:
+//!
+//! * With the `serde` feature, `Context` and `Digest` implement `Serialize`
+//!   and `Deserialize`.
+//! * `Context::deserialize` restores a context from a `ContextData` and
+//!   returns an error for invalid data.
 
 // Note on why are we doing things the hard way: It would be easy to implement
 // this using the C `EVP_MD`/`EVP_MD_CTX` interface. However, if we were to do
@@ -30,32 +43,125 @@ use self::{
 };
 
 use crate::{
-    bits::{BitLength, FromByteLen as _},
-    cpu, debug,
+    constant_time, cpu, debug, error,
     polyfill::{self, slice, sliceutil},
 };
 use core::num::Wrapping;
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+pub use self::{
+    builder::AlgorithmBuilder,
+    canonical::{CanonicalHash, CanonicalHasher},
+    checksum::{crc32, crc32c},
+    commitment::{commit, verify_commitment, Commitment, Opening, COMMITMENT_SALT_LEN},
+    hasher::{DigestBuildHasher, DigestHasher},
+    hex::ParseDigestError,
+    limited::{LimitExceeded, LimitedContext},
+    midstate::Midstate,
+    profiling::{ByteHistogram, ProfilingContext},
+    result::{
+        BufferTooSmall, CustomAlgorithm, DigestError, DigestResult, LengthOverflow,
+        TruncationError, VerificationError,
+    },
+    sha256_x8::sha256_x8,
+    transcript::Transcript,
+    unbuffer::CannotUnbuffer,
+    view::{StateView, StateWords},
+};
+
+#[cfg(feature = "serialize")]
+pub use self::binary::DeserializeError;
+
+#[cfg(feature = "educational")]
+pub use self::educational::{trace_block256, BlockTrace};
+
+#[cfg(feature = "std")]
+pub use self::{
+    channel::digest_receiver,
+    demux::DemuxHasher,
+    parallel::digest_auto,
+    signatures::{file_block_signatures, first_differing_block},
+    tee::TeeHashingWriter,
+};
+
+#[cfg(feature = "alloc")]
+pub use self::{batch::digest_batch, multi::MultiContext};
+
+/// Derives [`CanonicalHash`] for a struct by hashing each of its fields in
+/// declaration order. Fields marked `#[hash(skip)]` are left out.
+#[cfg(feature = "derive")]
+pub use ringctx_derive::CanonicalHash;
+
 #[cfg(any(feature = "serde", feature = "serialize"))]
 pub use ctx_serialize::ContextData;
 
+mod base32;
+#[cfg(feature = "alloc")]
+mod batch;
+#[cfg(feature = "serialize")]
+mod binary;
+mod builder;
+mod canonical;
+#[cfg(feature = "std")]
+mod channel;
+mod checksum;
+mod commitment;
+#[cfg(feature = "std")]
+mod demux;
 mod dynstate;
+#[cfg(feature = "educational")]
+mod educational;
+mod encoding;
+mod hasher;
+mod hex;
+mod limited;
+mod midstate;
+#[cfg(feature = "alloc")]
+mod multi;
+#[cfg(feature = "std")]
+mod parallel;
+mod profiling;
+#[cfg(feature = "std")]
+mod read;
+mod result;
 mod sha1;
 mod sha2;
+mod sha256_x8;
+#[cfg(feature = "std")]
+mod signatures;
+#[cfg(feature = "std")]
+mod tee;
+mod transcript;
+pub mod tree;
+mod unbuffer;
+#[cfg(all(test, feature = "verify-intrinsics", target_arch = "x86_64"))]
+mod verify_intrinsics;
+mod view;
+#[cfg(feature = "std")]
+mod write;
 
 #[derive(Clone)]
 pub(crate) struct BlockContext {
     state: DynState,
 
-    // Note that SHA-512 has a 128-bit input bit counter, but this
-    // implementation only supports up to 2^64-1 input bits for all algorithms,
-    // so a 64-bit counter is more than sufficient.
-    completed_bytes: u64,
+    // SHA-384 and SHA-512 have a 128-bit input bit counter, so they support
+    // up to 2^128-1 input bits; the other algorithms support up to 2^64-1.
+    // `finish` checks that the length fits in the algorithm's counter.
+    completed_bytes: u128,
 
     /// The context's algorithm.
     pub algorithm: &'static Algorithm,
 }
 
+#[cfg(feature = "zeroize")]
+impl Drop for BlockContext {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.state);
+    }
+}
+
 impl BlockContext {
     pub(crate) fn new(algorithm: &'static Algorithm) -> Self {
         Self {
@@ -75,11 +181,50 @@ impl BlockContext {
         // a bit length.
         self.completed_bytes = self
             .completed_bytes
-            .saturating_add(polyfill::u64_from_usize(completed_bytes));
+            .saturating_add(u128::from(polyfill::u64_from_usize(completed_bytes)));
         leftover
     }
 
+    /// Whether an input of `len` bytes has a bit length that fits in the
+    /// final block's length field.
+    fn bit_len_fits(&self, len: u128) -> bool {
+        len.checked_mul(8).map_or(false, |bits| {
+            let bits = bits.to_be_bytes();
+            let (high, _) = bits.split_at(bits.len() - self.algorithm.len_len);
+            high.iter().all(|&b| b == 0)
+        })
+    }
+
+    /// Writes the length, in bits, of the input absorbed so far plus
+    /// `num_pending` bytes to `out` in big-endian order, as the final block's
+    /// length field, which is `self.algorithm.len_len` bytes long.
+    ///
+    /// Panics if the length doesn't fit in the length field.
+    fn write_bit_len(&self, num_pending: usize, out: &mut [u8]) {
+        let completed_bits = self
+            .completed_bytes
+            .checked_add(u128::from(polyfill::u64_from_usize(num_pending)))
+            .and_then(|completed_bytes| completed_bytes.checked_mul(8))
+            .unwrap();
+        let completed_bits = completed_bits.to_be_bytes();
+        let (high, low) = completed_bits.split_at(completed_bits.len() - out.len());
+        assert!(high.iter().all(|&b| b == 0));
+        out.copy_from_slice(low);
+    }
+
     pub(crate) fn finish(
+        self,
+        pending: &mut [u8],
+        num_pending: usize,
+        cpu_features: cpu::Features,
+    ) -> Digest {
+        (self.algorithm.finalize)(self, pending, num_pending, cpu_features)
+    }
+
+    /// The Merkle–Damgård finalization shared by SHA-1 and SHA-2: appends
+    /// the `0x80` byte, zeros, and the big-endian bit length, then
+    /// processes the final block(s).
+    fn finish_md(
         mut self,
         pending: &mut [u8],
         num_pending: usize,
@@ -103,22 +248,18 @@ impl BlockContext {
             padding_pos = 0;
         }
 
-        pending[padding_pos..(block_len - 8)].fill(0);
+        let len_start = block_len - self.algorithm.len_len;
+        pending[padding_pos..len_start].fill(0);
 
         // Output the length, in bits, in big endian order.
-        let completed_bytes = self
-            .completed_bytes
-            .checked_add(polyfill::u64_from_usize(num_pending))
-            .unwrap();
-        let copmleted_bits = BitLength::from_byte_len(completed_bytes).unwrap();
-        pending[(block_len - 8)..].copy_from_slice(&copmleted_bits.to_be_bytes());
+        self.write_bit_len(num_pending, &mut pending[len_start..]);
 
         let (completed_bytes, leftover) = self.block_data_order(pending, cpu_features);
         debug_assert_eq!((completed_bytes, leftover.len()), (block_len, 0));
 
         Digest {
             algorithm: self.algorithm,
-            value: (self.algorithm.format_output)(self.state),
+            value: (self.algorithm.format_output)(self.state.clone()),
         }
     }
 
@@ -157,15 +298,33 @@ pub struct Context {
 
     // Invariant: `self.num_pending < self.block.algorithm.block_len`.
     num_pending: usize,
+
+    // Set by `seal`; further updates are a logic error.
+    sealed: bool,
+
+    // Set when `update_text` held back a trailing `\r`, which hasn't been
+    // hashed yet, to see whether the next call starts with `\n`.
+    pending_cr: bool,
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Context {
+    fn drop(&mut self) {
+        // `self.block` zeroizes its own state.
+        zeroize::Zeroize::zeroize(&mut self.pending);
+    }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Context {}
+
 #[cfg(any(feature = "serde", feature = "serialize"))]
 mod ctx_serialize {
     use crate::digest::dynstate::DynState;
     use crate::digest::sha2::{State32, State64};
     use crate::digest::{
-        AlgorithmID, BlockContext, Context, SHA1_FOR_LEGACY_USE_ONLY, SHA256, SHA384, SHA512,
-        SHA512_256,
+        encoding, sha2::CHAINING_WORDS, Algorithm, AlgorithmId, BlockContext, Context,
+        CustomAlgorithm, DeserializeError, DigestResult, StateView, StateWords, MAX_BLOCK_LEN,
     };
     use alloc::string::{String, ToString};
     use alloc::vec::Vec;
@@ -174,30 +333,83 @@ mod ctx_serialize {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     /// Structure used to store and restore Context
+    ///
+    /// When deserializing with serde, fields that this version doesn't know
+    /// about are ignored, so that checkpoints written by a newer version
+    /// that only adds fields can still be read. Don't add
+    /// `#[serde(deny_unknown_fields)]` here.
     #[derive(Clone)]
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct ContextData {
+        /// The version of the representation, [`Self::CURRENT_VERSION`]
+        /// when created by this version of the crate. Version 1, which
+        /// stored `completed_bytes` in 64 bits, is also accepted; contexts
+        /// with other versions are rejected when deserialized. Data stored
+        /// before this field existed is version 1.
+        #[cfg_attr(feature = "serde", serde(default = "ContextData::version_1"))]
+        pub version: u32,
         /// Context state name
         pub state_name: String,
         /// Context state data
         pub state_data: Vec<u64>,
         /// Completed bytes
-        pub completed_bytes: u64,
-        /// Digest algorithm name = AlgorithmID
-        pub algorithm: String,
+        pub completed_bytes: u128,
+        /// Digest algorithm
+        pub algorithm: AlgorithmId,
         /// Number of pending bytes
         pub num_pending: usize,
         /// Pending bytes
         pub pending: Vec<u8>,
     }
 
+    /// Overwrites the state and the pending bytes with zeros, leaving
+    /// `state_data` and `pending` empty.
+    #[cfg(feature = "zeroize")]
+    impl zeroize::Zeroize for ContextData {
+        fn zeroize(&mut self) {
+            self.state_data.zeroize();
+            self.completed_bytes.zeroize();
+            self.num_pending.zeroize();
+            self.pending.zeroize();
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    impl Drop for ContextData {
+        fn drop(&mut self) {
+            zeroize::Zeroize::zeroize(self);
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    impl zeroize::ZeroizeOnDrop for ContextData {}
+
+    impl ContextData {
+        /// The version of the representation that this version of the
+        /// crate creates.
+        pub const CURRENT_VERSION: u32 = encoding::CURRENT_VERSION;
+
+        /// Whether this version of the crate can restore contexts with
+        /// representation `version`.
+        pub(crate) fn is_supported_version(version: u32) -> bool {
+            (1..=Self::CURRENT_VERSION).contains(&version)
+        }
+
+        #[cfg(feature = "serde")]
+        fn version_1() -> u32 {
+            1
+        }
+    }
+
     #[cfg(feature = "serde")]
     impl Serialize for Context {
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
             S: Serializer,
         {
-            ContextData::from(self).serialize(serializer)
+            ContextData::try_from(self)
+                .map_err(serde::ser::Error::custom)?
+                .serialize(serializer)
         }
     }
 
@@ -208,80 +420,289 @@ mod ctx_serialize {
             D: Deserializer<'de>,
         {
             let c_data = ContextData::deserialize(deserializer)?;
-            Ok(Context::from(c_data))
+            Context::deserialize(c_data).map_err(serde::de::Error::custom)
+        }
+    }
+
+    /// A `ContextData` whose algorithm name hasn't been parsed yet, for
+    /// [`Context::deserialize_with_hook`].
+    #[cfg(feature = "serde")]
+    #[derive(Deserialize)]
+    struct RawContextData {
+        #[serde(default = "ContextData::version_1")]
+        version: u32,
+        state_name: String,
+        state_data: Vec<u64>,
+        completed_bytes: u128,
+        algorithm: String,
+        num_pending: usize,
+        pending: Vec<u8>,
+    }
+
+    /// Fails for a custom algorithm, which has no [`AlgorithmId`].
+    impl TryFrom<&Context> for ContextData {
+        type Error = CustomAlgorithm;
+
+        fn try_from(value: &Context) -> Result<Self, Self::Error> {
+            Self::try_from(value.state_view())
         }
     }
 
-    impl From<&Context> for ContextData {
-        fn from(value: &Context) -> Self {
-            let (state_name, state_data) = match value.block.state {
-                DynState::As64(as64) => ("as64", as64.iter().map(|w| w.0).collect::<Vec<_>>()),
-                DynState::As32(as32) => (
+    /// Fails for a custom algorithm, which has no [`AlgorithmId`].
+    impl TryFrom<StateView<'_>> for ContextData {
+        type Error = CustomAlgorithm;
+
+        fn try_from(value: StateView<'_>) -> Result<Self, Self::Error> {
+            let algorithm = value.algorithm().algorithm_id()?;
+            // Past `num_pending`, the buffer may still hold input that has
+            // already been absorbed or unbuffered; don't copy it.
+            let mut pending = alloc::vec![0u8; MAX_BLOCK_LEN];
+            pending[..value.num_pending].copy_from_slice(value.pending());
+            let (state_name, state_data) = match value.state_words() {
+                StateWords::As64(as64) => ("as64", as64.iter().map(|w| w.0).collect::<Vec<_>>()),
+                StateWords::As32(as32) => (
                     "as32",
                     as32.iter().map(|w| u64::from(w.0)).collect::<Vec<_>>(),
                 ),
             };
 
-            let algo = match value.block.algorithm.id {
-                AlgorithmID::SHA1 => "SHA1",
-                AlgorithmID::SHA256 => "SHA256",
-                AlgorithmID::SHA384 => "SHA384",
-                AlgorithmID::SHA512 => "SHA512",
-                AlgorithmID::SHA512_256 => "SHA512_256",
-            };
-
-            ContextData {
-                completed_bytes: value.block.completed_bytes,
+            Ok(ContextData {
+                version: ContextData::CURRENT_VERSION,
+                completed_bytes: value.completed_bytes(),
                 state_name: state_name.to_string(),
                 state_data,
-                algorithm: algo.to_string(),
+                algorithm,
                 num_pending: value.num_pending,
-                pending: value.pending.to_vec(),
-            }
+                pending,
+            })
         }
     }
 
-    impl From<ContextData> for Context {
-        fn from(data: ContextData) -> Self {
-            let algo = match data.algorithm.as_str() {
-                "SHA1" => &SHA1_FOR_LEGACY_USE_ONLY,
-                "SHA256" => &SHA256,
-                "SHA384" => &SHA384,
-                "SHA512" => &SHA512,
-                "SHA512_256" => &SHA512_256,
-                _ => &SHA256,
+    impl Context {
+        /// Hashes `salt` and returns the resulting checkpoint.
+        ///
+        /// This is intended for salted hashing with a fixed salt: compute
+        /// the checkpoint once and store it, then at startup restore it with
+        /// [`Context::deserialize`] and clone the restored context for each
+        /// message, so that the salt is never hashed again:
+        ///
+        /// ```
+        /// use ring::digest::{self, Context};
+        ///
+        /// let salt = [0x5a; 200];
+        /// let checkpoint = Context::warm_with_salt(&digest::SHA256, &salt).unwrap();
+        /// // ... store `checkpoint` and load it again later ...
+        /// let salted = Context::deserialize(checkpoint).unwrap();
+        ///
+        /// for message in [&b"first"[..], b"second"] {
+        ///     let mut ctx = salted.clone();
+        ///     ctx.update(message);
+        ///     let _digest = ctx.finish();
+        /// }
+        /// ```
+        ///
+        /// Fails for a custom algorithm, which can't be serialized.
+        pub fn warm_with_salt(
+            algorithm: &'static Algorithm,
+            salt: &[u8],
+        ) -> DigestResult<ContextData> {
+            let mut ctx = Self::new(algorithm);
+            ctx.update(salt);
+            Ok(ContextData::try_from(&ctx)?)
+        }
+
+        /// Restores a context from `data`.
+        ///
+        /// Fails if `data` doesn't describe a valid context: if its version
+        /// isn't supported (see [`ContextData::version`]), if `state_name`
+        /// isn't `"as32"` or `"as64"`, if `state_data` or `pending` has the
+        /// wrong length, if `num_pending` isn't less than the block length,
+        /// if a state word of a 32-bit state doesn't fit in 32 bits, if the
+        /// length of the input so far is more than the algorithm allows, or
+        /// if the state is otherwise inconsistent with the algorithm.
+        ///
+        /// [`Context::try_from`] does the same, failing with the
+        /// [`DeserializeError`] itself.
+        pub fn deserialize(data: ContextData) -> DigestResult<Self> {
+            Ok(Self::try_from(data)?)
+        }
+
+        /// Like deserializing a `Context` with serde, but when the stored
+        /// algorithm name isn't that of an [`AlgorithmId`], calls
+        /// `on_fallback` with the name and uses SHA-256 in its place instead
+        /// of failing.
+        ///
+        /// This is a transitional aid for finding stored contexts with bad
+        /// algorithm names, e.g. to log and count them, before switching to
+        /// the strict deserialization, which rejects them. The names of
+        /// custom algorithms, which earlier versions stored, are still
+        /// rejected without calling `on_fallback`, since their state can't
+        /// be restored as any other algorithm's.
+        #[cfg(feature = "serde")]
+        pub fn deserialize_with_hook<'de, D>(
+            deserializer: D,
+            mut on_fallback: impl FnMut(&str),
+        ) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let raw = RawContextData::deserialize(deserializer)?;
+            let algorithm = match raw.algorithm.parse::<AlgorithmId>() {
+                Ok(id) => id,
+                Err(_) if matches!(raw.algorithm.as_str(), "CUSTOM_SHA256" | "CUSTOM_SHA512") => {
+                    return Err(serde::de::Error::custom(
+                        DeserializeError::UnknownAlgorithm(raw.algorithm),
+                    ));
+                }
+                Err(_) => {
+                    on_fallback(&raw.algorithm);
+                    AlgorithmId::SHA256
+                }
+            };
+            let data = ContextData {
+                version: raw.version,
+                state_name: raw.state_name,
+                state_data: raw.state_data,
+                completed_bytes: raw.completed_bytes,
+                algorithm,
+                num_pending: raw.num_pending,
+                pending: raw.pending,
+            };
+            Context::deserialize(data).map_err(serde::de::Error::custom)
+        }
+
+        fn deserialize_for(
+            algo: &'static Algorithm,
+            data: ContextData,
+        ) -> Result<Self, DeserializeError> {
+            if !ContextData::is_supported_version(data.version) {
+                return Err(DeserializeError::UnsupportedVersion(data.version));
+            }
+
+            let bad_state_length = DeserializeError::BadStateLength {
+                expected: CHAINING_WORDS,
+                actual: data.state_data.len(),
+            };
+
+            // SHA-1 and SHA-256 use 32-bit words; the SHA-512 family uses
+            // 64-bit words. Reading the state at the wrong width would
+            // silently produce the wrong digest.
+            let is_as64 = match data.state_name.as_str() {
+                "as32" => false,
+                "as64" => true,
+                _ => return Err(DeserializeError::Malformed),
             };
+            if is_as64 != matches!(algo.initial_state, DynState::As64(_)) {
+                return Err(DeserializeError::StateWidthMismatch);
+            }
 
             let mut block = BlockContext::new(algo);
             block.completed_bytes = data.completed_bytes;
-            block.state = match data.state_name.as_str() {
-                "as64" => {
+            block.state = match is_as64 {
+                true => {
                     let state: State64 = data
                         .state_data
                         .iter()
                         .map(|b| Wrapping(*b))
                         .collect::<Vec<_>>()
                         .try_into()
-                        .unwrap();
+                        .map_err(|_| bad_state_length.clone())?;
                     DynState::As64(state)
                 }
-                _ => {
+                false => {
                     let state: State32 = data
                         .state_data
                         .iter()
-                        .map(|b| Wrapping(u32::try_from(*b).unwrap()))
-                        .collect::<Vec<_>>()
+                        .enumerate()
+                        .map(|(index, &word)| {
+                            u32::try_from(word)
+                                .map(Wrapping)
+                                .map_err(|_| DeserializeError::StateWordTooLarge { word, index })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?
                         .try_into()
-                        .unwrap();
+                        .map_err(|_| bad_state_length.clone())?;
                     DynState::As32(state)
                 }
             };
 
-            Self {
+            let pending = data.pending.as_slice().try_into().map_err(|_| {
+                DeserializeError::BadPendingLength {
+                    expected: MAX_BLOCK_LEN,
+                    actual: data.pending.len(),
+                }
+            })?;
+            // `pending` is `MAX_BLOCK_LEN` bytes, so this also ensures that
+            // `num_pending <= pending.len()`.
+            if data.num_pending >= algo.block_len() {
+                return Err(DeserializeError::InvalidNumPending);
+            }
+
+            let ctx = Self {
                 block,
-                pending: data.pending.try_into().unwrap(),
+                pending,
                 num_pending: data.num_pending,
+                sealed: false,
+                pending_cr: false,
+            };
+            ctx.debug_check_invariants()
+                .map_err(|_| DeserializeError::Malformed)?;
+            Ok(ctx)
+        }
+
+        /// Restores a context from a block-aligned checkpoint and the partial
+        /// block that followed it, stored separately.
+        ///
+        /// This suits storage that persists the committed state and the
+        /// in-flight tail at different cadences, e.g. the tail in a write-ahead
+        /// log. `state_checkpoint` must have no pending bytes, and `pending`
+        /// must be shorter than the algorithm's block length; otherwise this
+        /// fails with `DeserializeError::Malformed`.
+        pub fn with_state_and_pending(
+            state_checkpoint: &ContextData,
+            pending: &[u8],
+        ) -> DigestResult<Self> {
+            let mut ctx = Self::deserialize(state_checkpoint.clone())?;
+            if ctx.num_pending != 0 || pending.len() >= ctx.block.algorithm.block_len() {
+                return Err(DeserializeError::Malformed.into());
             }
+            ctx.pending[..pending.len()].copy_from_slice(pending);
+            ctx.num_pending = pending.len();
+            ctx.debug_check_invariants()
+                .map_err(|_| DeserializeError::Malformed)?;
+            Ok(ctx)
+        }
+    }
+
+    impl TryFrom<ContextData> for Context {
+        type Error = DeserializeError;
+
+        /// See [`Context::deserialize`].
+        fn try_from(data: ContextData) -> Result<Self, Self::Error> {
+            Self::deserialize_for(data.algorithm.algorithm(), data)
+        }
+    }
+
+    #[cfg(feature = "cbor")]
+    impl ContextData {
+        /// Encodes `self` as a CBOR map.
+        pub fn to_cbor(&self) -> Vec<u8> {
+            let mut out = Vec::new();
+            ciborium::into_writer(self, &mut out).unwrap();
+            out
+        }
+
+        /// Decodes a `ContextData` previously encoded with
+        /// [`Self::to_cbor`].
+        ///
+        /// Fails with `DeserializeError::Malformed` if `input` isn't a
+        /// well-formed encoding, or with the error from
+        /// [`Context::deserialize`] if it doesn't describe a valid context.
+        pub fn from_cbor(input: &[u8]) -> DigestResult<Self> {
+            let data: Self =
+                ciborium::from_reader(input).map_err(|_| DeserializeError::Malformed)?;
+            let _ = Context::deserialize(data.clone())?;
+            Ok(data)
         }
     }
 }
@@ -293,6 +714,8 @@ impl Context {
             block: BlockContext::new(algorithm),
             pending: [0u8; MAX_BLOCK_LEN],
             num_pending: 0,
+            sealed: false,
+            pending_cr: false,
         }
     }
 
@@ -301,17 +724,51 @@ impl Context {
             block: block.clone(),
             pending: [0u8; MAX_BLOCK_LEN],
             num_pending: 0,
+            sealed: false,
+            pending_cr: false,
+        }
+    }
+
+    /// The block context, if no input is buffered; the inverse of
+    /// `clone_from`.
+    #[cfg(feature = "serialize")]
+    pub(crate) fn block_context(&self) -> Option<&BlockContext> {
+        if self.num_pending == 0 {
+            Some(&self.block)
+        } else {
+            None
         }
     }
 
     /// Updates the digest with all the data in `data`.
+    ///
+    /// Panics in debug builds if the context has been [sealed](Self::seal);
+    /// in release builds, updating a sealed context does nothing.
     pub fn update(&mut self, data: &[u8]) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(algorithm = ?self.block.algorithm, len = data.len(), "update");
+
+        if self.sealed {
+            if cfg!(debug_assertions) {
+                panic!("digest::Context::update called on a sealed context");
+            }
+            return;
+        }
+        self.flush_cr();
+
         let cpu_features = cpu::features();
 
         let block_len = self.block.algorithm.block_len();
         let buffer = &mut self.pending[..block_len];
 
         let to_digest = if self.num_pending == 0 {
+            if data.len() < block_len {
+                // `data` can't complete a block, so just buffer it, skipping
+                // the `block_data_order` dispatch.
+                sliceutil::overwrite_at_start(buffer, data);
+                self.num_pending = data.len();
+                return;
+            }
             data
         } else {
             let buffer_to_fill = match buffer.get_mut(self.num_pending..) {
@@ -346,19 +803,383 @@ impl Context {
         debug_assert!(self.num_pending < block_len);
     }
 
+    /// Seals the context so that it can no longer be updated.
+    ///
+    /// This is a guardrail against logic errors where a context, or a clone
+    /// of it, keeps being updated after its input was meant to be complete.
+    /// After sealing, [`Self::update`] and the other update methods panic in
+    /// debug builds and do nothing in release builds. Sealing is copied by
+    /// `clone`, and a sealed context can still be finished.
+    pub fn seal(&mut self) {
+        self.flush_cr();
+        self.sealed = true;
+    }
+
+    /// Whether the context has been [sealed](Self::seal).
+    pub fn is_sealed(&self) -> bool {
+        self.sealed
+    }
+
+    /// Resets the context to the state of a new context for the same
+    /// algorithm, discarding any input, so that it can be reused.
+    ///
+    /// This also unseals a [sealed](Self::seal) context.
+    pub fn reset(&mut self) {
+        self.block.state = self.block.algorithm.initial_state.clone();
+        self.block.completed_bytes = 0;
+        self.num_pending = 0;
+        self.sealed = false;
+        self.pending_cr = false;
+    }
+
+    /// Updates the digest with `words`, each encoded in big-endian order.
+    pub fn update_u32_slice_be(&mut self, words: &[u32]) {
+        self.update_words(words, u32::to_be_bytes)
+    }
+
+    /// Updates the digest with `words`, each encoded in little-endian order.
+    pub fn update_u32_slice_le(&mut self, words: &[u32]) {
+        self.update_words(words, u32::to_le_bytes)
+    }
+
+    /// Updates the digest with `words`, each encoded in big-endian order.
+    pub fn update_u64_slice_be(&mut self, words: &[u64]) {
+        self.update_words(words, u64::to_be_bytes)
+    }
+
+    /// Updates the digest with `words`, each encoded in little-endian order.
+    pub fn update_u64_slice_le(&mut self, words: &[u64]) {
+        self.update_words(words, u64::to_le_bytes)
+    }
+
+    /// Updates the digest with `values`, canonicalized for reproducibility.
+    ///
+    /// Each value is canonicalized and then hashed as the big-endian encoding
+    /// of its IEEE 754 binary64 bit pattern, where:
+    ///
+    /// * every NaN, regardless of its sign and payload, is replaced by the
+    ///   quiet NaN `0x7ff8_0000_0000_0000`, and
+    /// * `-0.0` is replaced by `+0.0` (`0x0000_0000_0000_0000`).
+    ///
+    /// All other values, including infinities and subnormals, are hashed
+    /// as-is.
+    pub fn update_f64_slice(&mut self, values: &[f64]) {
+        const CANONICAL_NAN: u64 = 0x7ff8_0000_0000_0000;
+
+        self.update_words(values, |value: f64| {
+            let bits = if value.is_nan() {
+                CANONICAL_NAN
+            } else if value == 0.0 {
+                0
+            } else {
+                value.to_bits()
+            };
+            bits.to_be_bytes()
+        })
+    }
+
+    /// Updates the digest with `data` read in text mode, for compatibility
+    /// with the checksums that `sha256sum --text` and similar tools produce.
+    ///
+    /// On Windows, where reading a file in text mode translates `\r\n`
+    /// line endings to `\n`, each `\r\n` in `data` is hashed as `\n`. On
+    /// all other platforms, including Unix, text mode is the same as binary
+    /// mode and this is equivalent to [`Self::update`].
+    ///
+    /// A `\r\n` that is split across two calls is also normalized: a
+    /// trailing `\r` is held back until the next update, or until the
+    /// context is finished. The state seen by [`Self::state_view`] and by
+    /// serialization doesn't include a held-back `\r` until then.
+    pub fn update_text(&mut self, data: &[u8]) {
+        #[cfg(windows)]
+        self.update_normalizing_crlf(data);
+
+        #[cfg(not(windows))]
+        self.update(data);
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn update_normalizing_crlf(&mut self, data: &[u8]) {
+        let mut rest = data;
+        // The `\r` held back by the previous call is dropped if this call
+        // completes a `\r\n`, and is kept held back by an empty call.
+        if self.pending_cr {
+            match rest.first() {
+                Some(b'\n') => self.pending_cr = false,
+                Some(_) => {}
+                None => return,
+            }
+        }
+        while let Some(i) = rest.windows(2).position(|w| w == b"\r\n") {
+            self.update(&rest[..i]);
+            rest = &rest[(i + 1)..];
+        }
+        match rest.split_last() {
+            Some((b'\r', rest)) if !self.sealed => {
+                self.update(rest);
+                self.pending_cr = true;
+            }
+            _ => self.update(rest),
+        }
+    }
+
+    /// Hashes the `\r` held back by `update_text`, if any.
+    fn flush_cr(&mut self) {
+        if core::mem::take(&mut self.pending_cr) {
+            self.update(b"\r");
+        }
+    }
+
+    /// Encodes `words` using `f` into a stack buffer, a block at a time, and
+    /// updates the digest with the encoded bytes.
+    fn update_words<W: Copy, F, const N: usize>(&mut self, words: &[W], f: F)
+    where
+        F: Fn(W) -> [u8; N],
+    {
+        let mut buffer = [0u8; MAX_BLOCK_LEN];
+        for words in words.chunks(MAX_BLOCK_LEN / N) {
+            let buffer = &mut buffer[..(words.len() * N)];
+            buffer
+                .chunks_exact_mut(N)
+                .zip(words)
+                .for_each(|(b, w)| b.copy_from_slice(&f(*w)));
+            self.update(buffer);
+        }
+    }
+
     /// Finalizes the digest calculation and returns the digest value.
     ///
     /// `finish` consumes the context so it cannot be (mis-)used after `finish`
     /// has been called.
-    pub fn finish(mut self) -> Digest {
+    ///
+    /// Panics if more than the algorithm's maximum input length has been
+    /// input; see [`Self::try_finish`].
+    pub fn finish(self) -> Digest {
+        self.try_finish().unwrap()
+    }
+
+    /// Like [`Self::finish`], but fails instead of panicking if more than the
+    /// algorithm's maximum input length, 2^64 - 1 bits for SHA-1 and SHA-256
+    /// and 2^128 - 1 bits for the others, has been input.
+    pub fn try_finish(mut self) -> Result<Digest, LengthOverflow> {
+        self.flush_cr();
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "finish",
+            algorithm = ?self.block.algorithm,
+            len = self
+                .block
+                .completed_bytes
+                .saturating_add(u128::from(polyfill::u64_from_usize(self.num_pending))),
+        )
+        .entered();
+
+        if self.would_overflow(0) {
+            return Err(LengthOverflow);
+        }
+
         let cpu_features = cpu::features();
 
         let block_len = self.block.algorithm.block_len();
-        self.block.finish(
+        // `Context` may implement `Drop` (with `zeroize`), so `self.block`
+        // can't be moved out.
+        Ok(self.block.clone().finish(
             &mut self.pending[..block_len],
             self.num_pending,
             cpu_features,
-        )
+        ))
+    }
+
+    /// Finalizes the digest calculation and returns the digest value along
+    /// with the total number of bytes that were hashed.
+    ///
+    /// The length saturates at `u64::MAX`, which only SHA-384 and SHA-512
+    /// inputs can exceed.
+    pub fn finish_with_len(self) -> (Digest, u64) {
+        let len = self.len();
+        (self.finish(), len)
+    }
+
+    /// The number of bytes input so far.
+    ///
+    /// Saturates at `u64::MAX`, which only SHA-384 and SHA-512 inputs can
+    /// exceed.
+    pub fn len(&self) -> u64 {
+        let len = self
+            .block
+            .completed_bytes
+            .saturating_add(u128::from(polyfill::u64_from_usize(self.num_pending)))
+            .saturating_add(u128::from(self.pending_cr));
+        u64::try_from(len).unwrap_or(u64::MAX)
+    }
+
+    /// Whether no input has been absorbed yet.
+    pub fn is_empty(&self) -> bool {
+        self.block.completed_bytes == 0 && self.num_pending == 0 && !self.pending_cr
+    }
+
+    /// Whether inputting `additional` more bytes would exceed the maximum
+    /// input length of the algorithm, 2^64 - 1 bits for SHA-1 and SHA-256
+    /// and 2^128 - 1 bits for the others, so that `finish` would panic.
+    ///
+    /// `would_overflow(0)` tells whether the input so far is already too
+    /// long.
+    pub fn would_overflow(&self, additional: usize) -> bool {
+        let len = self
+            .block
+            .completed_bytes
+            .checked_add(u128::from(polyfill::u64_from_usize(self.num_pending)))
+            .and_then(|len| len.checked_add(u128::from(self.pending_cr)))
+            .and_then(|len| len.checked_add(u128::from(polyfill::u64_from_usize(additional))));
+        !len.map_or(false, |len| self.block.bit_len_fits(len))
+    }
+
+    /// Returns the chaining state as the lowercase hex encoding of its
+    /// big-endian words, the "midstate" format that tools like Bitcoin miners
+    /// print.
+    ///
+    /// The midstate only reflects complete blocks; buffered input that
+    /// doesn't yet fill a block isn't included. An error is returned for the
+    /// truncated algorithms, SHA-384 and SHA-512/256, since their midstates
+    /// are longer than their outputs and aren't conventionally printed.
+    #[cfg(feature = "alloc")]
+    pub fn midstate_hex(&self) -> Result<alloc::string::String, error::Unspecified> {
+        let algorithm = self.block.algorithm;
+        if algorithm.output_len() != algorithm.chaining_len {
+            return Err(error::Unspecified);
+        }
+        let state = (algorithm.format_output)(self.block.state.clone());
+        let state = &state.0[..algorithm.chaining_len];
+        let mut out = alloc::vec![0u8; 2 * state.len()];
+        hex::encode(state, &mut out);
+        alloc::string::String::from_utf8(out).map_err(|_| error::Unspecified)
+    }
+
+    /// The message length, in bits, that `finish` will encode in the final
+    /// block for the input absorbed so far.
+    ///
+    /// `finish` encodes this as a 64-bit (SHA-1, SHA-256) or 128-bit
+    /// (SHA-384, SHA-512, and the truncated SHA-512 variants) big-endian
+    /// integer, and panics if the length doesn't fit. Saturates at
+    /// `u128::MAX`.
+    pub fn pending_length_bits(&self) -> u128 {
+        self.block
+            .completed_bytes
+            .saturating_add(u128::from(polyfill::u64_from_usize(self.num_pending)))
+            .saturating_mul(8)
+    }
+
+    /// Returns true if finishing `self` and `other` now would produce equal
+    /// digests, without consuming either.
+    ///
+    /// This compares the actual output, so two contexts match even if their
+    /// internal states differ only in parts that a truncated algorithm, like
+    /// SHA-512/256, discards. Contexts using different algorithms never
+    /// match. This isn't constant-time.
+    pub fn will_match(&self, other: &Context) -> bool {
+        self.block.algorithm == other.block.algorithm
+            && self.clone().finish().as_ref() == other.clone().finish().as_ref()
+    }
+
+    /// Returns the exact bytes that `finish` would process in its final
+    /// compression function call(s): the buffered partial block followed by
+    /// the padding and the encoded message length.
+    ///
+    /// The result is one block long, or two blocks long when the padding
+    /// doesn't fit in the remainder of the current block. This is intended
+    /// for comparing finalization against other implementations.
+    ///
+    /// Panics if `finish` would panic because too much data has been input.
+    #[cfg(feature = "alloc")]
+    pub fn final_blocks(&self) -> Vec<u8> {
+        let algorithm = self.block.algorithm;
+        let block_len = algorithm.block_len();
+
+        let mut blocks = alloc::vec![0u8; 2 * block_len];
+        blocks[..self.num_pending].copy_from_slice(&self.pending[..self.num_pending]);
+        blocks[self.num_pending] = 0x80;
+        if self.num_pending < block_len - algorithm.len_len {
+            blocks.truncate(block_len);
+        }
+
+        // The length, in bits, in big endian order, as `finish` writes it.
+        let len_start = blocks.len() - algorithm.len_len;
+        self.block
+            .write_bit_len(self.num_pending, &mut blocks[len_start..]);
+
+        blocks
+    }
+
+    /// Finalizes the digest calculation and returns the first `len` bytes of
+    /// the digest value.
+    ///
+    /// Fails if `len` is larger than the algorithm's output length.
+    pub fn finish_truncated(self, len: usize) -> Result<TruncatedDigest, error::Unspecified> {
+        if len > self.algorithm().output_len() {
+            return Err(error::Unspecified);
+        }
+        let digest = self.finish();
+        Ok(TruncatedDigest {
+            value: digest.value,
+            algorithm: digest.algorithm,
+            len,
+        })
+    }
+
+    /// Finalizes the digest calculation and compares the digest value to
+    /// `expected` in constant time.
+    ///
+    /// Fails with `VerificationError::WrongLength` if `expected` isn't as
+    /// long as the algorithm's output, and with `VerificationError::Mismatch`
+    /// if the values differ.
+    pub fn finish_verify(self, expected: &[u8]) -> Result<(), VerificationError> {
+        let output_len = self.algorithm().output_len();
+        if expected.len() != output_len {
+            return Err(VerificationError::WrongLength {
+                expected: output_len,
+                actual: expected.len(),
+            });
+        }
+        if self.finish().verify_eq(expected) {
+            Ok(())
+        } else {
+            Err(VerificationError::Mismatch)
+        }
+    }
+
+    /// Derives the `index`th output from the input absorbed so far.
+    ///
+    /// The output is the first `out_len` bytes of the digest of the input
+    /// followed by the big-endian encoding of `index`. `self` isn't
+    /// modified, so many outputs can be derived from a shared prefix without
+    /// rehashing it. Fails if `out_len` is larger than the algorithm's output
+    /// length.
+    ///
+    /// This is only suitable for separating keys in non-adversarial settings;
+    /// it is **not** a substitute for a real KDF such as [`crate::hkdf`].
+    pub fn derive_subkey(
+        &self,
+        index: u32,
+        out_len: usize,
+    ) -> Result<TruncatedDigest, error::Unspecified> {
+        let mut ctx = self.clone();
+        ctx.update(&index.to_be_bytes());
+        ctx.finish_truncated(out_len)
+    }
+
+    /// Converts the context into a closure that finishes the digest
+    /// calculation.
+    ///
+    /// Calling the returned closure with `data` is equivalent to calling
+    /// `update(data)` followed by `finish()`. This allows the final step to
+    /// be handed to code that doesn't know which algorithm is being used.
+    pub fn into_finalizer(self) -> impl FnOnce(&[u8]) -> Digest {
+        move |data| {
+            let mut ctx = self;
+            ctx.update(data);
+            ctx.finish()
+        }
     }
 
     /// The algorithm that this context is using.
@@ -366,6 +1187,108 @@ impl Context {
     pub fn algorithm(&self) -> &'static Algorithm {
         self.block.algorithm
     }
+
+    /// The position within the current block, i.e. the number of bytes
+    /// absorbed since the last block boundary.
+    ///
+    /// This is always less than `self.algorithm().block_len()`. Absorbing
+    /// `block_len() - offset_in_block()` more bytes (or none, when this is
+    /// zero) brings the input to a block boundary.
+    #[inline(always)]
+    pub fn offset_in_block(&self) -> usize {
+        self.num_pending
+    }
+
+    /// Checks the internal consistency of the context.
+    ///
+    /// This is cheap and is intended to be used after a context has been
+    /// reconstructed from stored data. On failure, the returned message
+    /// describes which invariant was violated.
+    pub fn debug_check_invariants(&self) -> Result<(), &'static str> {
+        let algorithm = self.block.algorithm;
+        if self.num_pending >= algorithm.block_len() {
+            return Err("num_pending must be less than the block length");
+        }
+        if core::mem::discriminant(&self.block.state)
+            != core::mem::discriminant(&algorithm.initial_state)
+        {
+            return Err("state word width does not match the algorithm");
+        }
+        // Only full blocks are counted in `completed_bytes`.
+        let block_len = u128::from(polyfill::u64_from_usize(algorithm.block_len()));
+        if self.block.completed_bytes % block_len != 0 {
+            return Err("completed_bytes must be a multiple of the block length");
+        }
+        // Otherwise `finish` would panic.
+        let len = self
+            .block
+            .completed_bytes
+            .saturating_add(u128::from(polyfill::u64_from_usize(self.num_pending)));
+        if !self.block.bit_len_fits(len) {
+            return Err("the input must not be longer than the algorithm allows");
+        }
+        Ok(())
+    }
+
+    /// Returns a multi-line, human-readable report of the context's internal
+    /// state, for diagnostics: the algorithm, the number of completed and
+    /// buffered bytes, the chaining state words in hex, the buffered input
+    /// in hex, and whether the context is sealed.
+    ///
+    /// The format isn't stable and can't be parsed back; use
+    /// [`ContextData`] for that. The report contains buffered input and
+    /// enough state to continue the computation, so treat it as sensitive
+    /// as the input itself.
+    #[cfg(feature = "alloc")]
+    pub fn debug_dump(&self) -> alloc::string::String {
+        use core::fmt::Write as _;
+
+        let mut out = alloc::string::String::new();
+        let _ = writeln!(out, "algorithm: {:?}", self.block.algorithm);
+        let _ = writeln!(out, "completed_bytes: {}", self.block.completed_bytes);
+        let _ = writeln!(out, "num_pending: {}", self.num_pending);
+        out.push_str("state:");
+        match &self.block.state {
+            DynState::As32(state) => {
+                for Wrapping(w) in state {
+                    let _ = write!(out, " {:08x}", w);
+                }
+            }
+            DynState::As64(state) => {
+                for Wrapping(w) in state {
+                    let _ = write!(out, " {:016x}", w);
+                }
+            }
+        }
+        out.push_str("\npending: ");
+        let pending = &self.pending[..self.num_pending];
+        let mut pending_hex = alloc::vec![0u8; 2 * pending.len()];
+        hex::encode(pending, &mut pending_hex);
+        out.extend(pending_hex.iter().map(|&b| char::from(b)));
+        let _ = writeln!(out, "\nsealed: {}", self.sealed);
+        out
+    }
+
+    /// The width of the words in the context's internal chaining state.
+    pub fn state_width(&self) -> StateWidth {
+        match self.block.state {
+            DynState::As32(_) => StateWidth::Bits32,
+            DynState::As64(_) => StateWidth::Bits64,
+        }
+    }
+}
+
+/// The width of the words in a [`Context`]'s internal chaining state.
+///
+/// SHA-1 and SHA-256 use 32-bit words; SHA-384, SHA-512, and SHA-512/256 use
+/// 64-bit words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateWidth {
+    /// 32-bit words.
+    Bits32,
+
+    /// 64-bit words.
+    Bits64,
 }
 
 /// Returns the digest of `data` using the given digest algorithm.
@@ -384,38 +1307,597 @@ impl Context {
 /// # }
 /// ```
 pub fn digest(algorithm: &'static Algorithm, data: &[u8]) -> Digest {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("digest", algorithm = ?algorithm, len = data.len()).entered();
+
     let mut ctx = Context::new(algorithm);
     ctx.update(data);
     ctx.finish()
 }
 
-/// A calculated digest value.
+/// Calculates the digest of `data` and writes it to the start of `out`.
 ///
-/// Use [`Self::as_ref`] to get the value as a `&[u8]`.
-#[derive(Clone, Copy)]
-pub struct Digest {
-    value: Output,
-    algorithm: &'static Algorithm,
+/// Exactly `algorithm.output_len()` bytes are written; the rest of `out` is
+/// left unchanged. This neither allocates nor returns a [`Digest`], so the
+/// caller controls where the result is stored.
+///
+/// Fails, without writing anything, if `out` is shorter than
+/// `algorithm.output_len()`.
+pub fn digest_into(algorithm: &'static Algorithm, data: &[u8], out: &mut [u8]) -> DigestResult<()> {
+    let out = out
+        .get_mut(..algorithm.output_len())
+        .ok_or(BufferTooSmall)?;
+    out.copy_from_slice(digest(algorithm, data).as_ref());
+    Ok(())
 }
 
-impl Digest {
-    /// The algorithm that was used to calculate the digest value.
-    #[inline(always)]
-    pub fn algorithm(&self) -> &'static Algorithm {
-        self.algorithm
+/// Returns the lowercase hex encoding of the digest of `data`.
+///
+/// This is the same as `digest(algorithm, data).to_hex()`, and its output
+/// format won't change, so it's suitable for embedding the digest of an
+/// asset into generated source code from a build script.
+///
+/// # Examples:
+///
+/// In `build.rs`, with this crate as a build dependency:
+///
+/// ```no_run
+/// use ring::digest;
+/// use std::{env, fs, path::Path};
+///
+/// let asset = fs::read("assets/logo.png").unwrap();
+/// let out_dir = env::var_os("OUT_DIR").unwrap();
+/// fs::write(
+///     Path::new(&out_dir).join("asset_digests.rs"),
+///     format!(
+///         "pub const LOGO_SHA256: &str = {:?};\n",
+///         digest::hex_of(&digest::SHA256, &asset)
+///     ),
+/// )
+/// .unwrap();
+/// println!("cargo:rerun-if-changed=assets/logo.png");
+/// ```
+///
+/// The crate then uses
+/// `include!(concat!(env!("OUT_DIR"), "/asset_digests.rs"))` to get
+/// `LOGO_SHA256`.
+#[cfg(feature = "alloc")]
+pub fn hex_of(algorithm: &'static Algorithm, data: &[u8]) -> alloc::string::String {
+    digest(algorithm, data).to_hex()
+}
+
+/// Returns the digest of `data` and the digest of `data` read back-to-front.
+///
+/// The second digest is `H(reverse(data))`. It is calculated without
+/// allocating a reversed copy of `data`; instead, block-sized chunks are
+/// reversed, starting from the end of `data`, into a stack buffer.
+pub fn digest_forward_reverse(algorithm: &'static Algorithm, data: &[u8]) -> (Digest, Digest) {
+    let forward = digest(algorithm, data);
+
+    let mut reverse = Context::new(algorithm);
+    let mut buffer = [0u8; MAX_BLOCK_LEN];
+    for chunk in data.rchunks(algorithm.block_len()) {
+        let buffer = &mut buffer[..chunk.len()];
+        buffer
+            .iter_mut()
+            .zip(chunk.iter().rev())
+            .for_each(|(b, c)| *b = *c);
+        reverse.update(buffer);
     }
+
+    (forward, reverse.finish())
 }
 
-impl AsRef<[u8]> for Digest {
+/// Returns a digest identifying the path `indices` in a tree, e.g. a
+/// BIP-32/SLIP-10 style derivation path.
+///
+/// The digest is of a fixed domain tag followed by each index as a
+/// big-endian `u32`, so it doesn't collide with the digest of the same bytes
+/// hashed for another purpose. This is meant for stable, deterministic IDs,
+/// not for deriving keys.
+pub fn digest_path(algorithm: &'static Algorithm, indices: &[u32]) -> Digest {
+    const DOMAIN_TAG: &[u8] = b"ring digest_path v1\0";
+
+    let mut ctx = Context::new(algorithm);
+    ctx.update(DOMAIN_TAG);
+    ctx.update_u32_slice_be(indices);
+    ctx.finish()
+}
+
+/// Returns the digest of `data`, domain-separated by the type `T`.
+///
+/// The digest is of a fixed domain tag, then the length of
+/// `core::any::type_name::<T>()` as a big-endian `u64`, then that name, and
+/// then `data`. Hashing the encodings of different types with their own
+/// `T` keeps their digests from colliding even when the encodings do.
+///
+/// `type_name` isn't guaranteed to be stable across compiler versions, so
+/// these digests must only be compared within the same binary; don't store
+/// them or send them elsewhere.
+pub fn digest_typed<T: ?Sized + 'static>(algorithm: &'static Algorithm, data: &[u8]) -> Digest {
+    const DOMAIN_TAG: &[u8] = b"ring digest_typed v1\0";
+
+    let name = core::any::type_name::<T>();
+    let mut ctx = Context::new(algorithm);
+    ctx.update(DOMAIN_TAG);
+    ctx.update(&polyfill::u64_from_usize(name.len()).to_be_bytes());
+    ctx.update(name.as_bytes());
+    ctx.update(data);
+    ctx.finish()
+}
+
+/// Returns whether `data` hashes to `expected` with `algorithm`.
+///
+/// Returns `false` without hashing `data` if `expected` isn't
+/// `algorithm.output_len()` bytes long. Otherwise the digest of `data` is
+/// compared to `expected` in constant time.
+pub fn verify(algorithm: &'static Algorithm, data: &[u8], expected: &[u8]) -> bool {
+    if expected.len() != algorithm.output_len() {
+        return false;
+    }
+    let actual = digest(algorithm, data);
+    constant_time::verify_slices_are_equal(actual.as_ref(), expected).is_ok()
+}
+
+/// Returns the digest of `pattern` repeated `count` times.
+///
+/// The repetition is never materialized: whole copies of `pattern` are
+/// buffered on the stack and the buffer is hashed as many times as needed,
+/// so e.g. hashing a gigabyte of zeros doesn't allocate a gigabyte.
+pub fn digest_repeated(algorithm: &'static Algorithm, pattern: &[u8], count: u64) -> Digest {
+    let mut ctx = Context::new(algorithm);
+    if pattern.is_empty() {
+        return ctx.finish();
+    }
+
+    let mut buffer = [0u8; 16 * MAX_BLOCK_LEN];
+    let copies_per_buffer = buffer.len() / pattern.len();
+    if copies_per_buffer <= 1 {
+        (0..count).for_each(|_| ctx.update(pattern));
+        return ctx.finish();
+    }
+
+    buffer
+        .chunks_exact_mut(pattern.len())
+        .for_each(|copy| copy.copy_from_slice(pattern));
+    let full = &buffer[..(copies_per_buffer * pattern.len())];
+    let copies_per_buffer = polyfill::u64_from_usize(copies_per_buffer);
+    (0..(count / copies_per_buffer)).for_each(|_| ctx.update(full));
+    // `count % copies_per_buffer < copies_per_buffer`, which is a `usize`.
+    let rest = usize::try_from(count % copies_per_buffer).unwrap();
+    ctx.update(&full[..(rest * pattern.len())]);
+    ctx.finish()
+}
+
+/// Returns `H(message || len)`, where `len` is the length of `message` in
+/// bytes, encoded as an 8-byte big-endian integer.
+///
+/// The length suffix is part of the hashed message; it is separate from,
+/// and in addition to, the bit length that the algorithm's own padding
+/// encodes. This exists for interoperability with legacy schemes that use
+/// this construction; it isn't a MAC and shouldn't be used as one.
+pub fn digest_len_suffixed(algorithm: &'static Algorithm, message: &[u8]) -> Digest {
+    let mut ctx = Context::new(algorithm);
+    ctx.update(message);
+    ctx.update(&polyfill::u64_from_usize(message.len()).to_be_bytes());
+    ctx.finish()
+}
+
+/// Returns the digest of the concatenation of `chunks`.
+///
+/// This is intended for ropes and other chunked buffers. Each chunk is only
+/// borrowed through `AsRef<[u8]>`, so reference-counted chunk types like
+/// `Arc<[u8]>` and `Rc<[u8]>` are hashed in place without copying their
+/// bytes; the chunks don't need to be any particular length.
+pub fn digest_rope<I>(algorithm: &'static Algorithm, chunks: I) -> Digest
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut ctx = Context::new(algorithm);
+    chunks
+        .into_iter()
+        .for_each(|chunk| ctx.update(chunk.as_ref()));
+    ctx.finish()
+}
+
+/// How [`digest_with_hole`] treats the bytes inside the hole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HoleMode {
+    /// The hole is left out: `data[..hole.start]` is followed directly by
+    /// `data[hole.end..]`.
+    Omit,
+
+    /// The hole is hashed as the same number of zero bytes.
+    ZeroFill,
+}
+
+/// Returns the digest of `data` with the bytes in `hole` left out or zeroed,
+/// according to `mode`.
+///
+/// This is useful for hashing a structure that embeds a field, such as its
+/// own signature, that mustn't contribute to the digest. An error is returned
+/// if `hole` isn't a valid range within `data`; an empty hole is valid.
+pub fn digest_with_hole(
+    algorithm: &'static Algorithm,
+    data: &[u8],
+    hole: core::ops::Range<usize>,
+    mode: HoleMode,
+) -> Result<Digest, error::Unspecified> {
+    let before = data.get(..hole.start).ok_or(error::Unspecified)?;
+    let hole_len = data.get(hole.clone()).ok_or(error::Unspecified)?.len();
+    let after = &data[hole.end..];
+
+    let mut ctx = Context::new(algorithm);
+    ctx.update(before);
+    if mode == HoleMode::ZeroFill {
+        let zeros = [0u8; MAX_BLOCK_LEN];
+        let mut remaining = hole_len;
+        while remaining > 0 {
+            let len = core::cmp::min(remaining, zeros.len());
+            ctx.update(&zeros[..len]);
+            remaining -= len;
+        }
+    }
+    ctx.update(after);
+    Ok(ctx.finish())
+}
+
+/// Returns the digest of each `record_len`-byte record in `data`.
+///
+/// `data` must consist of zero or more contiguous records that are each
+/// exactly `record_len` bytes long; otherwise, or if `record_len` is zero, an
+/// error is returned. The digests are returned in the same order as the
+/// records.
+#[cfg(feature = "alloc")]
+pub fn digest_records(
+    algorithm: &'static Algorithm,
+    data: &[u8],
+    record_len: usize,
+) -> Result<Vec<Digest>, error::Unspecified> {
+    if record_len == 0 || data.len() % record_len != 0 {
+        return Err(error::Unspecified);
+    }
+    Ok(data
+        .chunks_exact(record_len)
+        .map(|record| digest(algorithm, record))
+        .collect())
+}
+
+/// Returns an order-independent fingerprint of `digests`.
+///
+/// The digest values are sorted by their bytes, concatenated, and hashed with
+/// `algorithm`, so two slices containing the same digests in any order have
+/// the same fingerprint. Duplicates are not removed: the fingerprint is of a
+/// multiset, so callers that want set semantics must deduplicate first.
+#[cfg(feature = "alloc")]
+pub fn set_fingerprint(algorithm: &'static Algorithm, digests: &[Digest]) -> Digest {
+    let mut values: Vec<&[u8]> = digests.iter().map(Digest::as_ref).collect();
+    values.sort_unstable();
+
+    let mut ctx = Context::new(algorithm);
+    values.iter().for_each(|value| ctx.update(value));
+    ctx.finish()
+}
+
+/// A calculated digest value.
+///
+/// Use [`Self::as_ref`] to get the value as a `&[u8]`.
+#[derive(Clone, Copy)]
+pub struct Digest {
+    value: Output,
+    algorithm: &'static Algorithm,
+}
+
+impl Digest {
+    /// The algorithm that was used to calculate the digest value.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// Writes the lowercase hex encoding of the digest value to `out`.
+    pub fn write_hex<W: core::fmt::Write>(&self, out: &mut W) -> core::fmt::Result {
+        let value = self.as_ref();
+        let mut buf = [0u8; 2 * MAX_OUTPUT_LEN];
+        let buf = &mut buf[..2 * value.len()];
+        hex::encode(value, buf);
+        out.write_str(core::str::from_utf8(buf).map_err(|_| core::fmt::Error)?)
+    }
+
+    /// Returns the lowercase hex encoding of the digest value.
+    #[cfg(feature = "alloc")]
+    pub fn to_hex(&self) -> alloc::string::String {
+        let mut out = alloc::string::String::with_capacity(2 * self.as_ref().len());
+        let _ = self.write_hex(&mut out);
+        out
+    }
+
+    /// Parses the hex encoding of a digest value calculated with
+    /// `algorithm`, in either case, e.g. as produced by [`Self::to_hex`].
+    ///
+    /// Fails if `hex` doesn't have exactly `2 * algorithm.output_len()`
+    /// characters or contains a character that isn't a hex digit.
+    pub fn from_hex(algorithm: &'static Algorithm, hex: &str) -> DigestResult<Self> {
+        let hex = hex.as_bytes();
+        if hex.len() % 2 != 0 {
+            return Err(ParseDigestError::OddLength.into());
+        }
+        let expected = 2 * algorithm.output_len();
+        if hex.len() != expected {
+            return Err(ParseDigestError::WrongLength {
+                expected,
+                actual: hex.len(),
+            }
+            .into());
+        }
+        let mut value = Output([0u8; MAX_OUTPUT_LEN]);
+        hex::decode(hex, &mut value.0[..algorithm.output_len()])?;
+        Ok(Self { value, algorithm })
+    }
+
+    /// Returns the uppercase hex encoding of the digest value.
+    #[cfg(feature = "alloc")]
+    pub fn to_hex_upper(&self) -> alloc::string::String {
+        let mut out = self.to_hex();
+        out.make_ascii_uppercase();
+        out
+    }
+
+    /// Returns the first `len` bytes of the digest value, e.g. a 128-bit
+    /// prefix of a SHA-256 digest.
+    ///
+    /// Fails if `len` is larger than the algorithm's output length; a
+    /// fixed-length digest can't be extended.
+    pub fn truncated(&self, len: usize) -> Result<&[u8], TruncationError> {
+        self.as_ref().get(..len).ok_or(TruncationError)
+    }
+
+    /// XOR-folds the digest value down to `out_len` bytes.
+    ///
+    /// Byte `i` of the digest value is XORed into byte `i % out_len` of the
+    /// output, so unlike truncation every output byte depends on the whole
+    /// digest. This isn't a standard construction; prefer
+    /// [`Context::finish_truncated`] where interoperability matters.
+    ///
+    /// Fails if `out_len` is zero or larger than the digest's output length.
+    #[cfg(feature = "alloc")]
+    pub fn xor_fold(&self, out_len: usize) -> Result<Vec<u8>, error::Unspecified> {
+        let value = self.as_ref();
+        if out_len == 0 || out_len > value.len() {
+            return Err(error::Unspecified);
+        }
+        let mut out = alloc::vec![0u8; out_len];
+        for chunk in value.chunks(out_len) {
+            out.iter_mut().zip(chunk).for_each(|(o, b)| *o ^= b);
+        }
+        Ok(out)
+    }
+
+    /// Returns `"<algorithm>:<value>"`, where `<algorithm>` is the lowercase
+    /// [`AlgorithmId`] name, e.g. `sha256`, and `<value>` is the unpadded,
+    /// lowercase base32 encoding of the digest value.
+    ///
+    /// This form is stable, and suitable for content-addressed identifiers
+    /// such as URLs. [`Self::from_uri`] parses it.
+    #[cfg(feature = "alloc")]
+    pub fn to_uri(&self) -> alloc::string::String {
+        let value = self.as_ref();
+        let mut encoded = [0u8; base32::encoded_len(MAX_OUTPUT_LEN)];
+        let encoded = &mut encoded[..base32::encoded_len(value.len())];
+        base32::encode(value, encoded);
+
+        let mut uri = self.algorithm.id.name().to_ascii_lowercase();
+        uri.push(':');
+        uri.extend(encoded.iter().map(|&b| char::from(b)));
+        uri
+    }
+
+    /// Parses a digest in the form produced by [`Self::to_uri`].
+    ///
+    /// Fails if the algorithm isn't recognized, if the value isn't
+    /// canonical unpadded, lowercase base32, or if it doesn't decode to
+    /// exactly the algorithm's output length. Digests of custom algorithms,
+    /// which have no [`AlgorithmId`], can't be parsed.
+    pub fn from_uri(uri: &str) -> DigestResult<Self> {
+        let (name, encoded) = uri.split_once(':').ok_or(error::Unspecified)?;
+        let algorithm = AlgorithmId::ALL
+            .into_iter()
+            .find(|id| {
+                let expected = id.name().bytes().map(|b| b.to_ascii_lowercase());
+                name.bytes().eq(expected)
+            })
+            .map(AlgorithmId::algorithm)
+            .ok_or(error::Unspecified)?;
+
+        let mut value = Output([0u8; MAX_OUTPUT_LEN]);
+        base32::decode(encoded.as_bytes(), &mut value.0[..algorithm.output_len()])?;
+        Ok(Self { value, algorithm })
+    }
+
+    /// Consumes the digest, returning its value buffer, the length of the
+    /// valid prefix of that buffer, and the algorithm's id.
+    ///
+    /// Bytes of the buffer after the valid prefix are unspecified.
+    /// [`Self::from_raw`] reverses this. Fails for a custom algorithm, which
+    /// has no id.
+    pub fn into_raw(self) -> DigestResult<([u8; MAX_OUTPUT_LEN], usize, AlgorithmId)> {
+        let id = self.algorithm.algorithm_id()?;
+        Ok((self.value.0, self.algorithm.output_len(), id))
+    }
+
+    /// Reconstructs a digest from the parts returned by [`Self::into_raw`].
+    ///
+    /// Fails if `len` isn't the output length of the algorithm identified by
+    /// `id`.
+    pub fn from_raw(
+        value: [u8; MAX_OUTPUT_LEN],
+        len: usize,
+        id: AlgorithmId,
+    ) -> Result<Self, error::Unspecified> {
+        let algorithm = id.algorithm();
+        if len != algorithm.output_len() {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            value: Output(value),
+            algorithm,
+        })
+    }
+
+    /// Returns whether the digest value equals `other`, comparing them in
+    /// constant time.
+    ///
+    /// Use this, rather than comparing `as_ref()` slices, whenever either
+    /// value is secret-dependent, e.g. when checking a received MAC-like tag
+    /// against the expected digest. Only the lengths are compared in
+    /// variable time. `==` on two `Digest`s also compares the values in
+    /// constant time, after comparing their algorithms.
+    pub fn verify_eq(&self, other: &[u8]) -> bool {
+        constant_time::verify_slices_are_equal(self.as_ref(), other).is_ok()
+    }
+}
+
+/// Formats the digest value as lowercase hex.
+impl core::fmt::Display for Digest {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.write_hex(fmt)
+    }
+}
+
+impl AsRef<[u8]> for Digest {
     #[inline(always)]
     fn as_ref(&self) -> &[u8] {
         &self.value.0[..self.algorithm.output_len()]
     }
 }
 
+/// Compares the algorithms and then, in constant time, the values.
+///
+/// Digests of different algorithms are never equal. Only the comparison
+/// of the values is constant-time; see [`Digest::verify_eq`].
+impl PartialEq for Digest {
+    fn eq(&self, other: &Self) -> bool {
+        self.algorithm == other.algorithm && self.verify_eq(other.as_ref())
+    }
+}
+
+impl Eq for Digest {}
+
+/// Hashes the algorithm's id and the value, consistently with `Eq`.
+impl core::hash::Hash for Digest {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.algorithm.id.hash(state);
+        self.as_ref().hash(state);
+    }
+}
+
+/// Orders digests by [`AlgorithmId`] and then by value, for use in ordered
+/// collections; this comparison isn't constant-time.
+///
+/// Digests of two different custom algorithms with the same id are ordered
+/// by the algorithms' addresses, which is consistent within a process but
+/// otherwise arbitrary.
+impl Ord for Digest {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.algorithm
+            .id
+            .cmp(&other.algorithm.id)
+            .then_with(|| {
+                if self.algorithm == other.algorithm {
+                    return core::cmp::Ordering::Equal;
+                }
+                let (a, b): (*const Algorithm, *const Algorithm) =
+                    (self.algorithm, other.algorithm);
+                a.cmp(&b)
+            })
+            .then_with(|| self.as_ref().cmp(other.as_ref()))
+    }
+}
+
+impl PartialOrd for Digest {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl core::fmt::Debug for Digest {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(fmt, "{:?}:", self.algorithm)?;
+        self.write_hex(fmt)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod digest_serde {
+    use super::{AlgorithmId, Digest};
+    use alloc::string::String;
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// How a `Digest` is represented, e.g. in JSON as
+    /// `{"algorithm":"SHA256","value":"<lowercase hex>"}`.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Digest")]
+    struct DigestData {
+        algorithm: AlgorithmId,
+        value: String,
+    }
+
+    /// Fails for a custom algorithm, which has no id.
+    impl Serialize for Digest {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            DigestData {
+                algorithm: self.algorithm.algorithm_id().map_err(ser::Error::custom)?,
+                value: self.to_hex(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    /// Fails if `value` isn't the hex encoding of `output_len()` bytes.
+    impl<'de> Deserialize<'de> for Digest {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let data = DigestData::deserialize(deserializer)?;
+            Digest::from_hex(data.algorithm.algorithm(), &data.value).map_err(de::Error::custom)
+        }
+    }
+}
+
+/// A digest value truncated to a caller-chosen length.
+///
+/// Use [`Self::as_ref`] to get the truncated value as a `&[u8]`.
+#[derive(Clone, Copy)]
+pub struct TruncatedDigest {
+    value: Output,
+    algorithm: &'static Algorithm,
+    // Invariant: `self.len <= self.algorithm.output_len()`.
+    len: usize,
+}
+
+impl TruncatedDigest {
+    /// The algorithm that was used to calculate the digest value.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+}
+
+impl AsRef<[u8]> for TruncatedDigest {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        &self.value.0[..self.len]
+    }
+}
+
+impl core::fmt::Debug for TruncatedDigest {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{:?}/{}:", self.algorithm, self.len * 8)?;
         debug::write_hex_bytes(fmt, self.as_ref())
     }
 }
@@ -440,23 +1922,134 @@ pub struct Algorithm {
 
     format_output: fn(input: DynState) -> Output,
 
+    /// `finalize` pads the buffered input in `pending[..num_pending]`,
+    /// processes the final block(s), and returns the digest. `pending` is
+    /// exactly `block_len` bytes long and may be used as scratch space. All
+    /// the current algorithms use `BlockContext::finish_md`; an algorithm
+    /// with a different construction, e.g. a sponge, would supply its own.
+    finalize: fn(
+        block: BlockContext,
+        pending: &mut [u8],
+        num_pending: usize,
+        cpu_features: cpu::Features,
+    ) -> Digest,
+
     initial_state: DynState,
 
-    id: AlgorithmID,
+    id: Identity,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum AlgorithmID {
+/// Identifies a digest algorithm, e.g. in a stored `ContextData`.
+///
+/// `Display` and `FromStr` use the variant names, e.g. `"SHA256"`, which
+/// are also how serde represents the variants. Ids are ordered as the
+/// variants are declared.
+///
+/// Algorithms built with [`AlgorithmBuilder`] have no id, so contexts and
+/// digests using them can't be serialized.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum AlgorithmId {
+    /// [`SHA1_FOR_LEGACY_USE_ONLY`].
     SHA1,
+    /// [`SHA256`].
     SHA256,
+    /// [`SHA384`].
     SHA384,
+    /// [`SHA512`].
     SHA512,
+    /// [`SHA512_256`].
     SHA512_256,
+    /// [`SHA512_224`].
+    SHA512_224,
+}
+
+impl AlgorithmId {
+    const ALL: [Self; 6] = [
+        Self::SHA1,
+        Self::SHA256,
+        Self::SHA384,
+        Self::SHA512,
+        Self::SHA512_256,
+        Self::SHA512_224,
+    ];
+
+    /// The algorithm with this id.
+    fn algorithm(self) -> &'static Algorithm {
+        match self {
+            Self::SHA1 => &SHA1_FOR_LEGACY_USE_ONLY,
+            Self::SHA256 => &SHA256,
+            Self::SHA384 => &SHA384,
+            Self::SHA512 => &SHA512,
+            Self::SHA512_256 => &SHA512_256,
+            Self::SHA512_224 => &SHA512_224,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::SHA1 => "SHA1",
+            Self::SHA256 => "SHA256",
+            Self::SHA384 => "SHA384",
+            Self::SHA512 => "SHA512",
+            Self::SHA512_256 => "SHA512_256",
+            Self::SHA512_224 => "SHA512_224",
+        }
+    }
+}
+
+impl core::fmt::Display for AlgorithmId {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl core::str::FromStr for AlgorithmId {
+    type Err = error::Unspecified;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|id| id.name() == s)
+            .ok_or(error::Unspecified)
+    }
+}
+
+/// Identifies an [`Algorithm`], including a custom one, which has no
+/// [`AlgorithmId`].
+#[derive(Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+enum Identity {
+    Known(AlgorithmId),
+
+    // Built with `AlgorithmBuilder`.
+    CustomSha256,
+    CustomSha512,
+}
+
+impl Identity {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Known(id) => id.name(),
+            Self::CustomSha256 => "CUSTOM_SHA256",
+            Self::CustomSha512 => "CUSTOM_SHA512",
+        }
+    }
+}
+
+impl core::fmt::Debug for Identity {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl PartialEq for Algorithm {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        match self.id {
+            Identity::Known(_) => self.id == other.id,
+            // Custom algorithms are distinguished by identity.
+            Identity::CustomSha256 | Identity::CustomSha512 => core::ptr::eq(self, other),
+        }
     }
 }
 
@@ -485,6 +2078,42 @@ impl Algorithm {
     pub fn output_len(&self) -> usize {
         self.output_len.into()
     }
+
+    /// The number of times the compression function is invoked to hash
+    /// `input_len` bytes, including the final block(s) of padding.
+    ///
+    /// Hashing takes one extra block when the padding and the encoded
+    /// length don't fit in the remainder of the last block.
+    pub fn block_count_for(&self, input_len: u64) -> u64 {
+        let block_len = polyfill::u64_from_usize(self.block_len());
+        let len_len = polyfill::u64_from_usize(self.len_len);
+        let final_blocks = if input_len % block_len < block_len - len_len {
+            1
+        } else {
+            2
+        };
+        input_len / block_len + final_blocks
+    }
+
+    /// The algorithm identified by `id`.
+    pub fn from_id(id: AlgorithmId) -> &'static Self {
+        id.algorithm()
+    }
+
+    /// The algorithm whose [`AlgorithmId`] is named `name`, e.g. `"SHA256"`.
+    ///
+    /// Returns `None` if `name` isn't the name of an `AlgorithmId`.
+    pub fn from_name(name: &str) -> Option<&'static Self> {
+        name.parse().ok().map(Self::from_id)
+    }
+
+    /// The algorithm's id, unless it's a custom algorithm.
+    fn algorithm_id(&self) -> Result<AlgorithmId, CustomAlgorithm> {
+        match self.id {
+            Identity::Known(id) => Ok(id),
+            Identity::CustomSha256 | Identity::CustomSha512 => Err(CustomAlgorithm),
+        }
+    }
 }
 
 /// SHA-1 as specified in [FIPS 180-4]. Deprecated.
@@ -497,6 +2126,7 @@ pub static SHA1_FOR_LEGACY_USE_ONLY: Algorithm = Algorithm {
     len_len: 64 / 8,
     block_data_order: dynstate::sha1_block_data_order,
     format_output: dynstate::sha256_format_output,
+    finalize: BlockContext::finish_md,
     initial_state: DynState::new32([
         Wrapping(0x67452301u32),
         Wrapping(0xefcdab89u32),
@@ -507,7 +2137,7 @@ pub static SHA1_FOR_LEGACY_USE_ONLY: Algorithm = Algorithm {
         Wrapping(0),
         Wrapping(0),
     ]),
-    id: AlgorithmID::SHA1,
+    id: Identity::Known(AlgorithmId::SHA1),
 };
 
 /// SHA-256 as specified in [FIPS 180-4].
@@ -520,6 +2150,7 @@ pub static SHA256: Algorithm = Algorithm {
     len_len: 64 / 8,
     block_data_order: dynstate::sha256_block_data_order,
     format_output: dynstate::sha256_format_output,
+    finalize: BlockContext::finish_md,
     initial_state: DynState::new32([
         Wrapping(0x6a09e667u32),
         Wrapping(0xbb67ae85u32),
@@ -530,7 +2161,7 @@ pub static SHA256: Algorithm = Algorithm {
         Wrapping(0x1f83d9abu32),
         Wrapping(0x5be0cd19u32),
     ]),
-    id: AlgorithmID::SHA256,
+    id: Identity::Known(AlgorithmId::SHA256),
 };
 
 /// SHA-384 as specified in [FIPS 180-4].
@@ -543,6 +2174,7 @@ pub static SHA384: Algorithm = Algorithm {
     len_len: SHA512_LEN_LEN,
     block_data_order: dynstate::sha512_block_data_order,
     format_output: dynstate::sha512_format_output,
+    finalize: BlockContext::finish_md,
     initial_state: DynState::new64([
         Wrapping(0xcbbb9d5dc1059ed8),
         Wrapping(0x629a292a367cd507),
@@ -553,7 +2185,7 @@ pub static SHA384: Algorithm = Algorithm {
         Wrapping(0xdb0c2e0d64f98fa7),
         Wrapping(0x47b5481dbefa4fa4),
     ]),
-    id: AlgorithmID::SHA384,
+    id: Identity::Known(AlgorithmId::SHA384),
 };
 
 /// SHA-512 as specified in [FIPS 180-4].
@@ -566,6 +2198,7 @@ pub static SHA512: Algorithm = Algorithm {
     len_len: SHA512_LEN_LEN,
     block_data_order: dynstate::sha512_block_data_order,
     format_output: dynstate::sha512_format_output,
+    finalize: BlockContext::finish_md,
     initial_state: DynState::new64([
         Wrapping(0x6a09e667f3bcc908),
         Wrapping(0xbb67ae8584caa73b),
@@ -576,7 +2209,7 @@ pub static SHA512: Algorithm = Algorithm {
         Wrapping(0x1f83d9abfb41bd6b),
         Wrapping(0x5be0cd19137e2179),
     ]),
-    id: AlgorithmID::SHA512,
+    id: Identity::Known(AlgorithmId::SHA512),
 };
 
 /// SHA-512/256 as specified in [FIPS 180-4].
@@ -593,6 +2226,7 @@ pub static SHA512_256: Algorithm = Algorithm {
     len_len: SHA512_LEN_LEN,
     block_data_order: dynstate::sha512_block_data_order,
     format_output: dynstate::sha512_format_output,
+    finalize: BlockContext::finish_md,
     initial_state: DynState::new64([
         Wrapping(0x22312194fc2bf72c),
         Wrapping(0x9f555fa3c84c64c2),
@@ -603,7 +2237,34 @@ pub static SHA512_256: Algorithm = Algorithm {
         Wrapping(0x2b0199fc2c85b8aa),
         Wrapping(0x0eb72ddc81c52ca2),
     ]),
-    id: AlgorithmID::SHA512_256,
+    id: Identity::Known(AlgorithmId::SHA512_256),
+};
+
+/// SHA-512/224 as specified in [FIPS 180-4].
+///
+/// Like [`SHA512_256`], this has its own initial state, so it isn't the
+/// same as truncating the output of SHA-512.
+///
+/// [FIPS 180-4]: http://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf
+pub static SHA512_224: Algorithm = Algorithm {
+    output_len: OutputLen::_224,
+    chaining_len: SHA512_OUTPUT_LEN,
+    block_len: SHA512_BLOCK_LEN,
+    len_len: SHA512_LEN_LEN,
+    block_data_order: dynstate::sha512_block_data_order,
+    format_output: dynstate::sha512_format_output,
+    finalize: BlockContext::finish_md,
+    initial_state: DynState::new64([
+        Wrapping(0x8c3d37c819544da2),
+        Wrapping(0x73e1996689dcd4d6),
+        Wrapping(0x1dfab7ae32ff9c82),
+        Wrapping(0x679dd514582f9fcf),
+        Wrapping(0x0f6d2b697bd44da8),
+        Wrapping(0x77e36f7304c48942),
+        Wrapping(0x3f9d85a86a1d36c8),
+        Wrapping(0x1112e6ad91d692a1),
+    ]),
+    id: Identity::Known(AlgorithmId::SHA512_224),
 };
 
 #[derive(Clone, Copy)]
@@ -653,6 +2314,9 @@ pub const SHA512_OUTPUT_LEN: usize = OutputLen::_512.into();
 /// The length of the output of SHA-512/256, in bytes.
 pub const SHA512_256_OUTPUT_LEN: usize = OutputLen::_256.into();
 
+/// The length of the output of SHA-512/224, in bytes.
+pub const SHA512_224_OUTPUT_LEN: usize = OutputLen::_224.into();
+
 /// The length of the length field for SHA-512-based algorithms, in bytes.
 const SHA512_LEN_LEN: usize = 128 / 8;
 
@@ -673,6 +2337,7 @@ impl BlockLen {
 #[derive(Clone, Copy)]
 enum OutputLen {
     _160 = 160 / 8,
+    _224 = 224 / 8,
     _256 = 256 / 8,
     _384 = 384 / 8,
     _512 = 512 / 8, // MAX
@@ -692,6 +2357,8 @@ mod tests {
     #[cfg(any(feature = "serde", feature = "serialize"))]
     mod store_restore_context {
         use crate::digest;
+        #[cfg(feature = "serialize")]
+        use crate::digest::DeserializeError;
         use crate::digest::{Context, ContextData, Digest, SHA256};
 
         fn compute_full_digest(alg: &'static digest::Algorithm, data: &[u8]) -> Digest {
@@ -721,6 +2388,863 @@ mod tests {
             assert_eq!(expected_digest.value.0, digest.value.0);
         }
 
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_serde_sha512_224() {
+            let data = [0xa5u8; 200];
+            let mut context = Context::new(&digest::SHA512_224);
+            context.update(&data[..150]);
+
+            let stored = serde_json::to_string(&context).unwrap();
+            assert!(stored.contains("\"SHA512_224\""), "{}", stored);
+            let mut restored: Context = serde_json::from_str(&stored).unwrap();
+            assert_eq!(restored.algorithm(), &digest::SHA512_224);
+            restored.update(&data[150..]);
+            assert_eq!(
+                restored.finish().as_ref(),
+                compute_full_digest(&digest::SHA512_224, &data).as_ref()
+            );
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_deserialize_invalid() {
+            let mut context = Context::new(&SHA256);
+            context.update(&[0x5a; 70]);
+            let stored = ContextData::try_from(&context).unwrap();
+            assert!(Context::deserialize(stored.clone()).is_ok());
+
+            // Custom algorithms have no id, so can't be stored at all.
+            let custom = alloc::boxed::Box::leak(alloc::boxed::Box::new(
+                digest::AlgorithmBuilder::sha256([1; 8]).build().unwrap(),
+            ));
+            assert_eq!(
+                ContextData::try_from(&Context::new(custom)).err(),
+                Some(digest::CustomAlgorithm)
+            );
+
+            let mut data = stored.clone();
+            data.pending.truncate(64);
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(
+                    DeserializeError::BadPendingLength {
+                        expected: digest::MAX_BLOCK_LEN,
+                        actual: 64
+                    }
+                    .into()
+                )
+            );
+
+            let mut data = stored.clone();
+            data.pending.push(0);
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(
+                    DeserializeError::BadPendingLength {
+                        expected: digest::MAX_BLOCK_LEN,
+                        actual: digest::MAX_BLOCK_LEN + 1
+                    }
+                    .into()
+                )
+            );
+
+            for alg in [&SHA256, &digest::SHA512] {
+                let mut data = ContextData::try_from(&Context::new(alg)).unwrap();
+                data.state_data.truncate(7);
+                assert_eq!(
+                    Context::deserialize(data).err(),
+                    Some(
+                        DeserializeError::BadStateLength {
+                            expected: 8,
+                            actual: 7
+                        }
+                        .into()
+                    )
+                );
+            }
+
+            let mut data = stored.clone();
+            data.state_data.push(0);
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(
+                    DeserializeError::BadStateLength {
+                        expected: 8,
+                        actual: 9
+                    }
+                    .into()
+                )
+            );
+
+            let mut data = stored.clone();
+            data.state_data.clear();
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(
+                    DeserializeError::BadStateLength {
+                        expected: 8,
+                        actual: 0
+                    }
+                    .into()
+                )
+            );
+
+            // SHA-256's block is 64 bytes, although `pending` is longer.
+            let mut data = stored.clone();
+            data.num_pending = SHA256.block_len();
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(DeserializeError::InvalidNumPending.into())
+            );
+
+            let mut data = stored.clone();
+            data.completed_bytes += 1;
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+
+            // A 64-bit state for a 32-bit algorithm.
+            let mut data = stored;
+            data.state_name = "as64".into();
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(DeserializeError::StateWidthMismatch.into())
+            );
+
+            // A 32-bit state for a 64-bit algorithm.
+            let mut data = ContextData::try_from(&Context::new(&digest::SHA384)).unwrap();
+            data.state_name = "as32".into();
+            assert_eq!(
+                Context::deserialize(data).err(),
+                Some(DeserializeError::StateWidthMismatch.into())
+            );
+
+            // A state name that is neither.
+            for name in ["as16", "AS32", ""] {
+                let mut data = ContextData::try_from(&Context::new(&SHA256)).unwrap();
+                data.state_name = name.into();
+                assert_eq!(
+                    Context::deserialize(data).err(),
+                    Some(DeserializeError::Malformed.into()),
+                    "{}",
+                    name
+                );
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_data_version() {
+            let mut context = Context::new(&SHA256);
+            context.update(&[0x5a; 70]);
+            let stored = ContextData::try_from(&context).unwrap();
+            assert_eq!(stored.version, ContextData::CURRENT_VERSION);
+
+            let mut future = stored.clone();
+            future.version = ContextData::CURRENT_VERSION + 1;
+            assert_eq!(
+                Context::deserialize(future.clone()).err(),
+                Some(DeserializeError::UnsupportedVersion(ContextData::CURRENT_VERSION + 1).into())
+            );
+            assert_eq!(
+                ContextData::from_bytes(&future.to_bytes()).err(),
+                Some(DeserializeError::UnsupportedVersion(ContextData::CURRENT_VERSION + 1).into())
+            );
+
+            let mut past = stored;
+            past.version = 0;
+            assert_eq!(
+                Context::deserialize(past).err(),
+                Some(DeserializeError::UnsupportedVersion(0).into())
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_data_serde_without_version() {
+            let data = [0x5au8; 100];
+            let mut context = Context::new(&SHA256);
+            context.update(&data[..70]);
+
+            // Data stored before the version was recorded is version 1.
+            let mut json = serde_json::to_value(ContextData::try_from(&context).unwrap()).unwrap();
+            let _ = json.as_object_mut().unwrap().remove("version").unwrap();
+            let decoded: ContextData = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded.version, 1);
+
+            let mut restored = Context::deserialize(decoded).unwrap();
+            restored.update(&data[70..]);
+            assert_eq!(
+                restored.finish().as_ref(),
+                compute_full_digest(&SHA256, &data).as_ref()
+            );
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_deserialize_num_pending_too_large() {
+            for alg in [
+                &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                &SHA256,
+                &digest::SHA384,
+                &digest::SHA512,
+                &digest::SHA512_256,
+            ] {
+                let mut context = Context::new(alg);
+                context.update(&[0x5a; 10]);
+                let stored = ContextData::try_from(&context).unwrap();
+
+                let mut data = stored.clone();
+                data.num_pending = alg.block_len() - 1;
+                assert!(Context::deserialize(data).is_ok());
+
+                for num_pending in [
+                    alg.block_len(),
+                    alg.block_len() + 1,
+                    digest::MAX_BLOCK_LEN,
+                    usize::MAX,
+                ] {
+                    let mut data = stored.clone();
+                    data.num_pending = num_pending;
+                    assert_eq!(
+                        Context::deserialize(data).err(),
+                        Some(DeserializeError::InvalidNumPending.into())
+                    );
+                }
+            }
+
+            // `num_pending` larger than `pending` is rejected too.
+            let mut data = ContextData::try_from(&Context::new(&SHA256)).unwrap();
+            data.pending.truncate(10);
+            data.num_pending = 20;
+            assert!(Context::deserialize(data).is_err());
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_data_algorithm_serde() {
+            use alloc::string::ToString;
+
+            for alg in [
+                &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                &SHA256,
+                &digest::SHA384,
+                &digest::SHA512,
+                &digest::SHA512_256,
+            ] {
+                let data = ContextData::try_from(&Context::new(alg)).unwrap();
+                let json = serde_json::to_value(&data).unwrap();
+                // The algorithm is stored by the same name as before it was
+                // typed, so existing checkpoints still load.
+                assert_eq!(json["algorithm"], data.algorithm.to_string());
+                let decoded: ContextData = serde_json::from_value(json).unwrap();
+                assert_eq!(decoded.algorithm, data.algorithm);
+                assert_eq!(Context::deserialize(decoded).unwrap().algorithm(), alg);
+            }
+
+            let mut json =
+                serde_json::to_value(ContextData::try_from(&Context::new(&SHA256)).unwrap())
+                    .unwrap();
+            json["algorithm"] = "SHA-256".into();
+            assert!(serde_json::from_value::<ContextData>(json).is_err());
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_serde_invalid_is_error() {
+            let mut data = ContextData::try_from(&Context::new(&SHA256)).unwrap();
+            data.pending.truncate(10);
+            let encoded = serde_json::to_string(&data).unwrap();
+            assert!(serde_json::from_str::<Context>(&encoded).is_err());
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_deserialize_with_hook() {
+            let data = [0x5au8; 100];
+            let mut context = Context::new(&SHA256);
+            context.update(&data[..70]);
+            let stored = serde_json::to_string(&context).unwrap();
+            let with_name =
+                |name: &str| stored.replace("\"SHA256\"", &alloc::format!("\"{name}\""));
+
+            let mut fallbacks = alloc::vec::Vec::new();
+            let mut hook = |name: &str| fallbacks.push(alloc::string::String::from(name));
+            let restore = |json: &str, hook: &mut dyn FnMut(&str)| {
+                let mut deserializer = serde_json::Deserializer::from_str(json);
+                Context::deserialize_with_hook(&mut deserializer, hook)
+            };
+
+            let _ = restore(&stored, &mut hook).unwrap();
+            let mut restored = restore(&with_name("sha-256"), &mut hook).unwrap();
+            // Custom algorithms are rejected, not restored as SHA-256.
+            assert!(restore(&with_name("CUSTOM_SHA256"), &mut hook).is_err());
+            assert_eq!(fallbacks, ["sha-256"]);
+
+            // The strict deserialization rejects the unknown name.
+            assert!(serde_json::from_str::<Context>(&with_name("sha-256")).is_err());
+
+            // The fallback is to SHA-256.
+            restored.update(&data[70..]);
+            assert_eq!(
+                restored.finish().as_ref(),
+                compute_full_digest(&SHA256, &data).as_ref()
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_data_serde_json() {
+            let data = [0x5au8; 300];
+            let mut context = Context::new(&SHA256);
+            context.update(&data[..100]);
+
+            let encoded = serde_json::to_string(&ContextData::try_from(&context).unwrap()).unwrap();
+
+            // The field names are part of the stored format.
+            let json: serde_json::Value = serde_json::from_str(&encoded).unwrap();
+            let mut fields: alloc::vec::Vec<&str> = json
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|k| k.as_str())
+                .collect();
+            fields.sort_unstable();
+            assert_eq!(
+                fields,
+                [
+                    "algorithm",
+                    "completed_bytes",
+                    "num_pending",
+                    "pending",
+                    "state_data",
+                    "state_name",
+                    "version"
+                ]
+            );
+
+            let decoded: ContextData = serde_json::from_str(&encoded).unwrap();
+            let mut restored = Context::deserialize(decoded).unwrap();
+            restored.update(&data[100..]);
+            assert_eq!(
+                restored.finish().as_ref(),
+                digest::digest(&SHA256, &data).as_ref()
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_serde_ignores_unknown_fields() {
+            let data = [0x5au8; 300];
+            let mut context = Context::new(&SHA256);
+            context.update(&data[..100]);
+
+            let mut json = serde_json::to_value(&context).unwrap();
+            assert!(json
+                .as_object_mut()
+                .unwrap()
+                .insert("future_field".into(), serde_json::json!({"version": 2}))
+                .is_none());
+            let encoded = serde_json::to_string(&json).unwrap();
+            assert!(encoded.contains("future_field"));
+
+            let mut restored: Context = serde_json::from_str(&encoded).unwrap();
+            restored.update(&data[100..]);
+            assert_eq!(
+                restored.finish().as_ref(),
+                compute_full_digest(&SHA256, &data).as_ref()
+            );
+
+            #[cfg(feature = "cbor")]
+            {
+                let mut encoded = alloc::vec::Vec::new();
+                ciborium::into_writer(&json, &mut encoded).unwrap();
+                let mut restored =
+                    Context::deserialize(ContextData::from_cbor(&encoded).unwrap()).unwrap();
+                restored.update(&data[100..]);
+                assert_eq!(
+                    restored.finish().as_ref(),
+                    compute_full_digest(&SHA256, &data).as_ref()
+                );
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_serialize_without_algorithm() {
+            let data = [0x42u8; 1000];
+            for alg in [&digest::SHA1_FOR_LEGACY_USE_ONLY, &SHA256, &digest::SHA512] {
+                for split in [0, 1, alg.block_len(), 2 * alg.block_len() + 3] {
+                    let mut context = Context::new(alg);
+                    context.update(&data[..split]);
+
+                    let stored = context.serialize_without_algorithm();
+                    let mut restored = Context::deserialize_with_algorithm(alg, &stored).unwrap();
+                    restored.update(&data[split..]);
+                    assert_eq!(
+                        restored.finish().as_ref(),
+                        compute_full_digest(alg, &data).as_ref()
+                    );
+                }
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_deserialize_with_algorithm_invalid() {
+            let mut context = Context::new(&SHA256);
+            context.update(&[0u8; 70]);
+            let stored = context.serialize_without_algorithm();
+
+            // Truncated.
+            assert!(
+                Context::deserialize_with_algorithm(&SHA256, &stored[..stored.len() - 1]).is_err()
+            );
+            assert!(Context::deserialize_with_algorithm(&SHA256, &[]).is_err());
+            // Wrong algorithm: the completed bytes aren't a multiple of the block length.
+            assert!(Context::deserialize_with_algorithm(&digest::SHA512, &stored).is_err());
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_deserialize_state_word_too_large() {
+            let mut context = Context::new(&SHA256);
+            context.update(&[0u8; 70]);
+            let mut stored = context.serialize_without_algorithm();
+
+            // Replace the third state word with 0x1_0000_0000.
+            let word = 0x1_0000_0000u64;
+            stored[(18 + 2 * 8)..(18 + 3 * 8)].copy_from_slice(&word.to_be_bytes());
+            assert_eq!(
+                Context::deserialize_with_algorithm(&SHA256, &stored).err(),
+                Some(DeserializeError::StateWordTooLarge { word, index: 2 }.into())
+            );
+
+            let mut stored = ContextData::try_from(&context).unwrap();
+            stored.state_data[2] = word;
+            assert_eq!(
+                Context::deserialize(stored).err(),
+                Some(DeserializeError::StateWordTooLarge { word, index: 2 }.into())
+            );
+
+            // SHA-1 only uses five words, but the unused ones are checked too.
+            let mut stored =
+                ContextData::try_from(&Context::new(&digest::SHA1_FOR_LEGACY_USE_ONLY)).unwrap();
+            stored.state_data[7] = word;
+            assert_eq!(
+                Context::deserialize(stored).err(),
+                Some(DeserializeError::StateWordTooLarge { word, index: 7 }.into())
+            );
+
+            // The same word is fine for a 64-bit algorithm.
+            let mut context = Context::new(&digest::SHA512);
+            context.update(&[0u8; 130]);
+            let mut stored = context.serialize_without_algorithm();
+            stored[(18 + 2 * 8)..(18 + 3 * 8)].copy_from_slice(&word.to_be_bytes());
+            assert!(Context::deserialize_with_algorithm(&digest::SHA512, &stored).is_ok());
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_context_serialize_without_algorithm_is_smaller() {
+            let mut context = Context::new(&SHA256);
+            context.update(b"hello, world");
+            let compact = context.serialize_without_algorithm();
+            let self_describing = serde_json::to_vec(&context).unwrap();
+            assert!(compact.len() < self_describing.len());
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_data_bytes() {
+            let mut context = Context::new(&SHA256);
+            context.update(b"abc");
+
+            let mut state = alloc::vec![];
+            for word in [
+                0x6a09e667u64,
+                0xbb67ae85,
+                0x3c6ef372,
+                0xa54ff53a,
+                0x510e527f,
+                0x9b05688c,
+                0x1f83d9ab,
+                0x5be0cd19,
+            ] {
+                state.extend_from_slice(&word.to_be_bytes());
+            }
+
+            let mut expected = alloc::vec![2, 2];
+            expected.extend_from_slice(&0u128.to_be_bytes());
+            expected.extend_from_slice(&3u16.to_be_bytes());
+            expected.extend_from_slice(&state);
+            expected.extend_from_slice(b"abc");
+
+            let encoded = ContextData::try_from(&context).unwrap().to_bytes();
+            assert_eq!(encoded, expected);
+
+            // Version 1 stored `completed_bytes` in 8 bytes.
+            let mut v1 = alloc::vec![1, 2];
+            v1.extend_from_slice(&0u64.to_be_bytes());
+            v1.extend_from_slice(&3u16.to_be_bytes());
+            v1.extend_from_slice(&state);
+            v1.extend_from_slice(b"abc");
+            let decoded = ContextData::from_bytes(&v1).unwrap();
+            assert_eq!(decoded.to_bytes(), encoded);
+            let mut v1_data = decoded;
+            v1_data.version = 1;
+            assert_eq!(v1_data.to_bytes(), v1);
+
+            // Truncated anywhere, including the empty input.
+            for len in 0..encoded.len() {
+                assert_eq!(
+                    ContextData::from_bytes(&encoded[..len]).err(),
+                    Some(DeserializeError::Malformed.into())
+                );
+            }
+
+            let mut bad_version = encoded.clone();
+            bad_version[0] = 3;
+            assert_eq!(
+                ContextData::from_bytes(&bad_version).err(),
+                Some(DeserializeError::UnsupportedVersion(3).into())
+            );
+            let mut bad_algorithm = encoded.clone();
+            bad_algorithm[1] = 0;
+            assert!(ContextData::from_bytes(&bad_algorithm).is_err());
+            // The tags once used for custom algorithms.
+            for tag in [6, 7] {
+                let mut custom = encoded.clone();
+                custom[1] = tag;
+                assert_eq!(
+                    ContextData::from_bytes(&custom).err(),
+                    Some(DeserializeError::Malformed.into())
+                );
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_data_bytes_round_trip() {
+            let data = [0x5au8; 300];
+            for alg in [
+                &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                &SHA256,
+                &digest::SHA384,
+                &digest::SHA512,
+                &digest::SHA512_256,
+                &digest::SHA512_224,
+            ] {
+                for split in [0, 1, 150, 256] {
+                    let mut context = Context::new(alg);
+                    context.update(&data[..split]);
+
+                    let encoded = ContextData::try_from(&context).unwrap().to_bytes();
+                    let decoded = ContextData::from_bytes(&encoded).unwrap();
+                    assert_eq!(
+                        decoded.algorithm,
+                        ContextData::try_from(&context).unwrap().algorithm
+                    );
+                    let mut restored = Context::deserialize(decoded).unwrap();
+                    restored.update(&data[split..]);
+                    assert_eq!(
+                        restored.finish().as_ref(),
+                        compute_full_digest(alg, &data).as_ref()
+                    );
+                }
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_serialize_into() {
+            let data = [0x5au8; 300];
+            let mut buf = [0u8; 2 + 18 + 64 + digest::MAX_BLOCK_LEN];
+            for alg in [&SHA256, &digest::SHA512] {
+                for split in [0, 1, 150, 256] {
+                    let mut context = Context::new(alg);
+                    context.update(&data[..split]);
+
+                    let len = context.serialize_into(&mut buf).unwrap();
+                    assert_eq!(len, context.serialized_len());
+                    assert_eq!(
+                        &buf[..len],
+                        ContextData::try_from(&context).unwrap().to_bytes()
+                    );
+                    assert_eq!(
+                        context.serialize_into(&mut buf[..len - 1]),
+                        Err(digest::BufferTooSmall.into())
+                    );
+
+                    let decoded = ContextData::from_bytes(&buf[..len]).unwrap();
+                    let mut restored = Context::deserialize(decoded).unwrap();
+                    restored.update(&data[split..]);
+                    assert_eq!(
+                        restored.finish().as_ref(),
+                        compute_full_digest(alg, &data).as_ref()
+                    );
+                }
+            }
+
+            let custom = alloc::boxed::Box::leak(alloc::boxed::Box::new(
+                digest::AlgorithmBuilder::sha512([1; 8]).build().unwrap(),
+            ));
+            let mut buf_before = buf;
+            assert_eq!(
+                Context::new(custom).serialize_into(&mut buf_before),
+                Err(digest::CustomAlgorithm.into())
+            );
+            assert_eq!(buf_before, buf);
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_data_omits_stale_pending() {
+            let mut context = Context::new(&SHA256);
+            // The first block passes through the buffer; 6 bytes follow it.
+            context.update(&[0x5a; 10]);
+            context.update(&[0xa5; 60]);
+            context.update(b"abcdef");
+            context.unbuffer(2).unwrap();
+            assert_eq!(context.pending[10..12], [0, 0]);
+
+            let data = ContextData::try_from(&context).unwrap();
+            assert_eq!(data.num_pending, 10);
+            assert_eq!(
+                data.pending[..10],
+                [0xa5, 0xa5, 0xa5, 0xa5, 0xa5, 0xa5, b'a', b'b', b'c', b'd']
+            );
+            assert!(data.pending[10..].iter().all(|&b| b == 0));
+        }
+
+        #[test]
+        fn test_state_view_matches_context_data() {
+            let data = [0x5au8; 300];
+            for alg in [
+                &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                &SHA256,
+                &digest::SHA384,
+                &digest::SHA512,
+                &digest::SHA512_256,
+                &digest::SHA512_224,
+            ] {
+                for split in [0, 1, 150, 256] {
+                    let mut context = Context::new(alg);
+                    context.update(&data[..split]);
+
+                    let view = context.state_view();
+                    let (state_name, state_data) = match view.state_words() {
+                        digest::StateWords::As32(words) => {
+                            ("as32", words.iter().map(|w| u64::from(w.0)).collect())
+                        }
+                        digest::StateWords::As64(words) => {
+                            ("as64", words.iter().map(|w| w.0).collect())
+                        }
+                    };
+                    let mut pending = view.pending().to_vec();
+                    pending.resize(digest::MAX_BLOCK_LEN, 0);
+                    let from_view = ContextData {
+                        version: ContextData::CURRENT_VERSION,
+                        state_name: state_name.into(),
+                        state_data,
+                        completed_bytes: view.completed_bytes(),
+                        algorithm: view.algorithm().algorithm_id().unwrap(),
+                        num_pending: view.pending().len(),
+                        pending,
+                    };
+
+                    let expected = ContextData::try_from(&context).unwrap();
+                    assert_eq!(from_view.state_name, expected.state_name);
+                    assert_eq!(from_view.state_data, expected.state_data);
+                    assert_eq!(from_view.completed_bytes, expected.completed_bytes);
+                    assert_eq!(from_view.algorithm, expected.algorithm);
+                    assert_eq!(from_view.num_pending, expected.num_pending);
+                    assert_eq!(from_view.pending, expected.pending);
+
+                    let mut restored = Context::deserialize(from_view).unwrap();
+                    restored.update(&data[split..]);
+                    assert_eq!(
+                        restored.finish().as_ref(),
+                        compute_full_digest(alg, &data).as_ref()
+                    );
+                }
+            }
+        }
+
+        #[cfg(feature = "cbor")]
+        #[test]
+        fn test_context_data_cbor_round_trip() {
+            let data = [0x5au8; 300];
+            for alg in [&digest::SHA1_FOR_LEGACY_USE_ONLY, &SHA256, &digest::SHA512] {
+                let mut context = Context::new(alg);
+                context.update(&data[..150]);
+
+                let encoded = ContextData::try_from(&context).unwrap().to_cbor();
+                let decoded = ContextData::from_cbor(&encoded).unwrap();
+                let mut restored = Context::deserialize(decoded).unwrap();
+                restored.update(&data[150..]);
+                assert_eq!(
+                    restored.finish().as_ref(),
+                    compute_full_digest(alg, &data).as_ref()
+                );
+            }
+        }
+
+        #[cfg(feature = "cbor")]
+        #[test]
+        fn test_context_data_cbor_malformed() {
+            let mut context = Context::new(&SHA256);
+            context.update(&[0u8; 70]);
+            let encoded = ContextData::try_from(&context).unwrap().to_cbor();
+
+            // Truncated.
+            assert_eq!(
+                ContextData::from_cbor(&encoded[..encoded.len() - 1]).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+            // Not CBOR at all.
+            assert_eq!(
+                ContextData::from_cbor(&[0xff, 0x00]).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+            assert_eq!(
+                ContextData::from_cbor(&[]).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+
+            // Well-formed CBOR that doesn't describe a valid context.
+            let mut data = ContextData::try_from(&context).unwrap();
+            data.num_pending = 64;
+            assert_eq!(
+                ContextData::from_cbor(&data.to_cbor()).err(),
+                Some(DeserializeError::InvalidNumPending.into())
+            );
+            let mut data = ContextData::try_from(&context).unwrap();
+            data.pending.truncate(10);
+            assert_eq!(
+                ContextData::from_cbor(&data.to_cbor()).err(),
+                Some(
+                    DeserializeError::BadPendingLength {
+                        expected: digest::MAX_BLOCK_LEN,
+                        actual: 10
+                    }
+                    .into()
+                )
+            );
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_warm_with_salt() {
+            let salt = [0xa5u8; 300];
+            let message = b"message";
+            for alg in [&digest::SHA1_FOR_LEGACY_USE_ONLY, &SHA256, &digest::SHA512] {
+                let checkpoint = Context::warm_with_salt(alg, &salt).unwrap();
+                let salted = Context::deserialize(checkpoint).unwrap();
+
+                let mut salted_message = salt.to_vec();
+                salted_message.extend_from_slice(message);
+                let expected = compute_full_digest(alg, &salted_message);
+
+                for _ in 0..2 {
+                    let mut ctx = salted.clone();
+                    ctx.update(message);
+                    assert_eq!(ctx.finish().as_ref(), expected.as_ref());
+                }
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_multi_context_round_trip() {
+            let algorithms = [&SHA256, &digest::SHA512, &digest::SHA1_FOR_LEGACY_USE_ONLY];
+            let data = [0x77u8; 500];
+            for split in [0, 1, 64, 200, 500] {
+                let mut ctx = digest::MultiContext::new(&algorithms).unwrap();
+                ctx.update(&data[..split]);
+
+                let mut restored =
+                    digest::MultiContext::deserialize(ctx.serialize().unwrap()).unwrap();
+                assert!(restored.algorithms().eq(algorithms.iter().copied()));
+                restored.update(&data[split..]);
+                let digests = restored.finish();
+                assert_eq!(digests.len(), algorithms.len());
+                for (digest, alg) in digests.iter().zip(algorithms) {
+                    assert_eq!(digest.as_ref(), compute_full_digest(alg, &data).as_ref());
+                }
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_multi_context_deserialize_invalid() {
+            let mut ctx = digest::MultiContext::new(&[&SHA256, &digest::SHA384]).unwrap();
+            ctx.update(&[1u8; 100]);
+            let stored = ctx.serialize().unwrap();
+
+            assert_eq!(
+                digest::MultiContext::deserialize(alloc::vec![]).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+
+            // Duplicate algorithm.
+            let duplicated = alloc::vec![stored[0].clone(), stored[0].clone()];
+            assert_eq!(
+                digest::MultiContext::deserialize(duplicated).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+
+            // Contexts that absorbed different amounts of input.
+            let mut other = Context::new(&digest::SHA384);
+            other.update(&[1u8; 99]);
+            let inconsistent =
+                alloc::vec![stored[0].clone(), ContextData::try_from(&other).unwrap()];
+            assert_eq!(
+                digest::MultiContext::deserialize(inconsistent).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+
+            assert!(digest::MultiContext::new(&[&SHA256, &digest::SHA512, &SHA256]).is_err());
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn test_context_with_state_and_pending() {
+            let data: [u8; 300] = core::array::from_fn(|i| i.to_le_bytes()[0]);
+            for alg in [&SHA256, &digest::SHA384, &digest::SHA512] {
+                let block_len = alg.block_len();
+                let mut context = Context::new(alg);
+                context.update(&data[..block_len]);
+                let checkpoint = ContextData::try_from(&context).unwrap();
+
+                for tail_len in [0, 1, block_len - 1] {
+                    let tail = &data[block_len..][..tail_len];
+                    let mut restored = Context::with_state_and_pending(&checkpoint, tail).unwrap();
+                    restored.update(&data[(block_len + tail_len)..]);
+                    assert_eq!(
+                        restored.finish().as_ref(),
+                        compute_full_digest(alg, &data).as_ref()
+                    );
+                }
+
+                // The tail must be a partial block.
+                assert_eq!(
+                    Context::with_state_and_pending(&checkpoint, &data[..block_len]).err(),
+                    Some(DeserializeError::Malformed.into())
+                );
+
+                // The checkpoint must be block-aligned.
+                context.update(&data[..1]);
+                let unaligned = ContextData::try_from(&context).unwrap();
+                assert_eq!(
+                    Context::with_state_and_pending(&unaligned, &[]).err(),
+                    Some(DeserializeError::Malformed.into())
+                );
+            }
+        }
+
         #[cfg(feature = "serialize")]
         #[test]
         fn test_context_serialization() {
@@ -733,15 +3257,447 @@ mod tests {
 
             // Compute and store half file context
             context.update(&license.as_bytes()[..len / 2]);
-            let stored_context = ContextData::from(&context);
+            let stored_context = ContextData::try_from(&context).unwrap();
 
-            context = Context::from(stored_context);
+            context = Context::try_from(stored_context).unwrap();
             context.update(&license.as_bytes()[len / 2..]);
             let digest = context.finish();
             assert_eq!(expected_digest.value.0, digest.value.0);
         }
     }
 
+    #[cfg(feature = "alloc")]
+    mod final_blocks {
+        use crate::{cpu, digest, polyfill};
+
+        #[test]
+        fn block_count_for_matches_compressions() {
+            for alg in [
+                &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                &digest::SHA256,
+                &digest::SHA384,
+                &digest::SHA512,
+                &digest::SHA512_256,
+            ] {
+                let block_len = alg.block_len();
+                let input = alloc::vec![0u8; 3 * block_len];
+                for len in (0..=2).flat_map(|n| {
+                    let boundary = n * block_len;
+                    let padding = block_len - alg.len_len;
+                    [
+                        boundary,
+                        boundary + 1,
+                        boundary + padding - 1,
+                        boundary + padding,
+                    ]
+                }) {
+                    let mut ctx = digest::Context::new(alg);
+                    ctx.update(&input[..len]);
+                    let completed_bytes = u64::try_from(ctx.block.completed_bytes).unwrap();
+                    let compressions = completed_bytes / polyfill::u64_from_usize(block_len)
+                        + polyfill::u64_from_usize(ctx.final_blocks().len() / block_len);
+                    assert_eq!(
+                        alg.block_count_for(polyfill::u64_from_usize(len)),
+                        compressions,
+                        "{:?} {}",
+                        alg,
+                        len
+                    );
+                }
+            }
+
+            // 55 bytes plus 0x80 and the 8-byte length fill one SHA-256 block
+            // exactly; 56 bytes don't.
+            assert_eq!(digest::SHA256.block_count_for(55), 1);
+            assert_eq!(digest::SHA256.block_count_for(56), 2);
+            assert_eq!(digest::SHA512.block_count_for(111), 1);
+            assert_eq!(digest::SHA512.block_count_for(112), 2);
+            assert_eq!(digest::SHA256.block_count_for(u64::MAX), u64::MAX / 64 + 2);
+        }
+
+        fn check_final_blocks(
+            alg: &'static digest::Algorithm,
+            input_len: usize,
+            expected_blocks: usize,
+        ) {
+            let input = alloc::vec![0xa5u8; input_len];
+            let mut ctx = digest::Context::new(alg);
+            ctx.update(&input);
+
+            let final_blocks = ctx.final_blocks();
+            assert_eq!(final_blocks.len(), expected_blocks * alg.block_len());
+            assert_eq!(
+                &final_blocks[..ctx.num_pending],
+                &input[input_len - ctx.num_pending..]
+            );
+            assert_eq!(final_blocks[ctx.num_pending], 0x80);
+
+            // Processing the final blocks must produce exactly what `finish`
+            // produces.
+            let mut block = ctx.block.clone();
+            let (completed, leftover) = block.block_data_order(&final_blocks, cpu::features());
+            assert_eq!((completed, leftover.len()), (final_blocks.len(), 0));
+            let expected = ctx.finish();
+            assert_eq!(
+                &(alg.format_output)(block.state.clone()).0[..alg.output_len()],
+                expected.as_ref()
+            );
+        }
+
+        #[test]
+        fn final_blocks_single_block() {
+            check_final_blocks(&digest::SHA256, 0, 1);
+            check_final_blocks(&digest::SHA256, 100, 1);
+            check_final_blocks(&digest::SHA512, 111, 1);
+        }
+
+        #[test]
+        fn final_blocks_padding_spills_into_extra_block() {
+            check_final_blocks(&digest::SHA1_FOR_LEGACY_USE_ONLY, 56, 2);
+            check_final_blocks(&digest::SHA256, 60, 2);
+            check_final_blocks(&digest::SHA256, 64 + 63, 2);
+            check_final_blocks(&digest::SHA384, 112, 2);
+        }
+    }
+
+    // `serialize_into` doesn't need the `serialize` feature.
+    mod encoding {
+        use crate::digest;
+
+        #[test]
+        fn serialize_into_layout() {
+            let mut context = digest::Context::new(&digest::SHA256);
+            context.update(&[0x5a; 64]);
+            context.update(b"abc");
+
+            let mut buf = [0xffu8; 2 + 18 + 64 + 3 + 1];
+            assert_eq!(context.serialize_into(&mut buf), Ok(buf.len() - 1));
+            assert_eq!(context.serialized_len(), buf.len() - 1);
+
+            let (header, rest) = buf.split_at(2);
+            assert_eq!(header, [2, 2]);
+            let (completed_bytes, rest) = rest.split_at(16);
+            assert_eq!(u128::from_be_bytes(completed_bytes.try_into().unwrap()), 64);
+            let (num_pending, rest) = rest.split_at(2);
+            assert_eq!(num_pending, [0, 3]);
+            let (state, rest) = rest.split_at(64);
+            for (bytes, w) in state
+                .chunks_exact(8)
+                .zip(context.block.state.to_u64_words())
+            {
+                assert_eq!(bytes, w.to_be_bytes());
+            }
+            assert_eq!(rest, b"abc\xff");
+
+            let len = context.serialized_len();
+            let mut short = [0u8; 2 + 18 + 64 + 2];
+            assert_eq!(
+                context.serialize_into(&mut short[..len - 1]),
+                Err(digest::BufferTooSmall.into())
+            );
+            assert!(short.iter().all(|&b| b == 0));
+        }
+    }
+
+    mod update_text {
+        use crate::digest;
+
+        fn normalized(data: &[u8]) -> digest::Digest {
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update_normalizing_crlf(data);
+            ctx.finish()
+        }
+
+        #[test]
+        fn crlf_normalized_to_lf() {
+            let cases: [(&[u8], &[u8]); 6] = [
+                (b"", b""),
+                (b"one line\r\n", b"one line\n"),
+                (b"a\r\nb\r\n\r\nc", b"a\nb\n\nc"),
+                // Lone carriage returns are kept.
+                (b"a\rb\r", b"a\rb\r"),
+                (b"\r\r\n\n", b"\r\n\n"),
+                (b"\r\n\r\n", b"\n\n"),
+            ];
+            for (crlf, lf) in cases {
+                assert_eq!(
+                    normalized(crlf).as_ref(),
+                    digest::digest(&digest::SHA256, lf).as_ref()
+                );
+            }
+
+            let lf: [u8; 500] = core::array::from_fn(|i| b"line\n"[i % 5]);
+            let crlf: [u8; 600] = core::array::from_fn(|i| b"line\r\n"[i % 6]);
+            assert_eq!(
+                normalized(&crlf).as_ref(),
+                digest::digest(&digest::SHA256, &lf).as_ref()
+            );
+        }
+
+        #[test]
+        fn crlf_split_across_calls() {
+            let cases: [(&[&[u8]], &[u8]); 6] = [
+                (&[b"a\r", b"\nb"], b"a\nb"),
+                (&[b"a\r", b"b"], b"a\rb"),
+                (&[b"a\r", b"\r\nb"], b"a\r\nb"),
+                (&[b"a\r", b"", b"\nb"], b"a\nb"),
+                (&[b"\r", b"\n", b"\r", b"\n"], b"\n\n"),
+                // A trailing `\r` is hashed by `finish`.
+                (&[b"a", b"\r"], b"a\r"),
+            ];
+            for (parts, lf) in cases {
+                let mut ctx = digest::Context::new(&digest::SHA256);
+                for part in parts {
+                    ctx.update_normalizing_crlf(part);
+                }
+                assert_eq!(
+                    ctx.finish().as_ref(),
+                    digest::digest(&digest::SHA256, lf).as_ref()
+                );
+            }
+        }
+
+        #[test]
+        fn held_back_cr() {
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update_normalizing_crlf(b"a\r");
+            assert_eq!(ctx.len(), 2);
+            ctx.unbuffer(1).unwrap();
+            assert_eq!(ctx.len(), 1);
+            ctx.update_normalizing_crlf(b"\nb");
+            assert_eq!(
+                ctx.finish().as_ref(),
+                digest::digest(&digest::SHA256, b"a\nb").as_ref()
+            );
+
+            // A plain `update` hashes the held-back `\r` first.
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update_normalizing_crlf(b"a\r");
+            ctx.update(b"\n");
+            assert_eq!(
+                ctx.finish().as_ref(),
+                digest::digest(&digest::SHA256, b"a\r\n").as_ref()
+            );
+        }
+
+        #[test]
+        fn update_text_matches_platform() {
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update_text(b"a\r\nb");
+            let expected: &[u8] = if cfg!(windows) { b"a\nb" } else { b"a\r\nb" };
+            assert_eq!(
+                ctx.finish().as_ref(),
+                digest::digest(&digest::SHA256, expected).as_ref()
+            );
+        }
+    }
+
+    mod invariants {
+        use crate::digest::{self, dynstate::DynState};
+        use core::num::Wrapping;
+
+        #[test]
+        fn check_invariants_valid() {
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            assert_eq!(ctx.debug_check_invariants(), Ok(()));
+            ctx.update(&[0u8; 100]);
+            assert_eq!(ctx.debug_check_invariants(), Ok(()));
+        }
+
+        #[test]
+        fn check_invariants_corrupted() {
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update(&[0u8; 100]);
+
+            let mut bad = ctx.clone();
+            bad.num_pending = digest::SHA256.block_len();
+            assert_eq!(
+                bad.debug_check_invariants(),
+                Err("num_pending must be less than the block length")
+            );
+
+            let mut bad = ctx.clone();
+            bad.block.state = DynState::new64([Wrapping(0); 8]);
+            assert_eq!(
+                bad.debug_check_invariants(),
+                Err("state word width does not match the algorithm")
+            );
+
+            let mut bad = ctx;
+            bad.block.completed_bytes += 1;
+            assert_eq!(
+                bad.debug_check_invariants(),
+                Err("completed_bytes must be a multiple of the block length")
+            );
+        }
+    }
+
+    mod finalize {
+        use crate::digest::{
+            self, dynstate, dynstate::DynState, Algorithm, BlockContext, Digest, Identity,
+            OutputLen, SHA256_BLOCK_LEN, SHA256_OUTPUT_LEN,
+        };
+        use crate::{cpu, test};
+        use core::num::Wrapping;
+
+        #[test]
+        fn md_finalize_unchanged() {
+            for (alg, expected) in [
+                (
+                    &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                    "a9993e364706816aba3e25717850c26c9cd0d89d",
+                ),
+                (
+                    &digest::SHA256,
+                    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                ),
+                (
+                    &digest::SHA384,
+                    "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+                     8086072ba1e7cc2358baeca134c825a7",
+                ),
+                (
+                    &digest::SHA512,
+                    "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                     2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+                ),
+                (
+                    &digest::SHA512_256,
+                    "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+                ),
+            ] {
+                let expected = test::from_hex(expected).unwrap();
+                assert_eq!(digest::digest(alg, b"abc").as_ref(), &expected[..]);
+                let mut ctx = digest::Context::new(alg);
+                ctx.update(b"abc");
+                assert_eq!(ctx.finish().as_ref(), &expected[..]);
+            }
+        }
+
+        // Outputs the chaining state without any padding, ignoring the
+        // buffered input.
+        fn unpadded_finalize(
+            block: BlockContext,
+            _pending: &mut [u8],
+            _num_pending: usize,
+            _cpu_features: cpu::Features,
+        ) -> Digest {
+            Digest {
+                algorithm: block.algorithm,
+                value: (block.algorithm.format_output)(block.state.clone()),
+            }
+        }
+
+        static UNPADDED: Algorithm = Algorithm {
+            output_len: OutputLen::_256,
+            chaining_len: SHA256_OUTPUT_LEN,
+            block_len: SHA256_BLOCK_LEN,
+            len_len: 64 / 8,
+            block_data_order: dynstate::sha256_block_data_order,
+            format_output: dynstate::sha256_format_output,
+            finalize: unpadded_finalize,
+            initial_state: DynState::new32([Wrapping(0); 8]),
+            id: Identity::CustomSha256,
+        };
+
+        #[test]
+        fn custom_finalize_is_used() {
+            let mut ctx = digest::Context::new(&UNPADDED);
+            ctx.update(b"abc");
+            assert_eq!(ctx.finish().as_ref(), &[0u8; SHA256_OUTPUT_LEN][..]);
+        }
+    }
+
+    mod large_update {
+        extern crate alloc;
+        use crate::{digest, polyfill::u64_from_usize};
+        use alloc::vec::Vec;
+
+        // A single `update` much larger than a block processes all the full
+        // blocks at once and buffers only the final partial block.
+        #[test]
+        fn large_update_test() {
+            for alg in [
+                &digest::SHA1_FOR_LEGACY_USE_ONLY,
+                &digest::SHA256,
+                &digest::SHA384,
+                &digest::SHA512,
+                &digest::SHA512_256,
+            ] {
+                let block_len = alg.block_len();
+                let input: Vec<u8> = (0..(1000 * block_len + 7))
+                    .map(|i| i.to_le_bytes()[0])
+                    .collect();
+
+                let mut large = digest::Context::new(alg);
+                large.update(&input);
+                assert_eq!(
+                    large.block.completed_bytes,
+                    1000 * u128::from(u64_from_usize(block_len))
+                );
+                assert_eq!(large.num_pending, 7);
+                assert_eq!(&large.pending[..7], &input[(1000 * block_len)..]);
+
+                let mut byte_at_a_time = digest::Context::new(alg);
+                input.chunks(1).for_each(|byte| byte_at_a_time.update(byte));
+                assert_eq!(
+                    byte_at_a_time.block.completed_bytes,
+                    large.block.completed_bytes
+                );
+                assert_eq!(byte_at_a_time.num_pending, large.num_pending);
+
+                let large = large.finish();
+                assert_eq!(large.as_ref(), byte_at_a_time.finish().as_ref());
+                assert_eq!(large.as_ref(), digest::digest(alg, &input).as_ref());
+            }
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    mod zeroize_on_drop {
+        use crate::digest::{self, dynstate::DynState};
+        use core::mem::ManuallyDrop;
+
+        fn assert_zeroize_on_drop<T: zeroize::ZeroizeOnDrop>() {}
+
+        #[test]
+        fn context_zeroized_on_drop() {
+            assert_zeroize_on_drop::<digest::Context>();
+
+            for alg in [&digest::SHA256, &digest::SHA512] {
+                let mut ctx = ManuallyDrop::new(digest::Context::new(alg));
+                ctx.update(&[0x5a; 200]);
+                assert!(ctx.pending[..ctx.num_pending].iter().any(|&b| b != 0));
+
+                // SAFETY: `ctx` isn't used after this, other than to inspect
+                // its fields, none of which own memory.
+                unsafe { core::ptr::drop_in_place(&mut *ctx) };
+
+                assert!(ctx.pending.iter().all(|&b| b == 0));
+                match &ctx.block.state {
+                    DynState::As64(state) => assert!(state.iter().all(|w| w.0 == 0)),
+                    DynState::As32(state) => assert!(state.iter().all(|w| w.0 == 0)),
+                }
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn context_data_zeroize() {
+            use zeroize::Zeroize;
+
+            assert_zeroize_on_drop::<digest::ContextData>();
+
+            let mut ctx = digest::Context::new(&digest::SHA256);
+            ctx.update(&[0x5a; 100]);
+            let mut data = digest::ContextData::try_from(&ctx).unwrap();
+            data.zeroize();
+            assert!(data.state_data.is_empty());
+            assert!(data.pending.is_empty());
+            assert_eq!((data.completed_bytes, data.num_pending), (0, 0));
+        }
+    }
+
     mod max_input {
         extern crate alloc;
         use super::super::super::digest;
@@ -758,6 +3714,16 @@ mod tests {
                         super::max_input_test(&digest::$algorithm_name);
                     }
 
+                    #[test]
+                    fn too_long_input_test_try_finish() {
+                        super::too_long_input_test_try_finish(&digest::$algorithm_name);
+                    }
+
+                    #[test]
+                    fn would_overflow_test() {
+                        super::would_overflow_test(&digest::$algorithm_name);
+                    }
+
                     #[test]
                     #[should_panic]
                     fn too_long_input_test_block() {
@@ -795,13 +3761,39 @@ mod tests {
             let _ = context.finish(); // should panic
         }
 
+        fn too_long_input_test_try_finish(alg: &'static digest::Algorithm) {
+            let mut context = nearly_full_context(alg);
+            context.update(&vec![0u8; alg.block_len() - 1]);
+            assert!(context.clone().try_finish().is_ok());
+            context.update(&[0]);
+            assert_eq!(context.try_finish().err(), Some(digest::LengthOverflow));
+        }
+
+        fn would_overflow_test(alg: &'static digest::Algorithm) {
+            let mut context = nearly_full_context(alg);
+            assert!(!context.would_overflow(alg.block_len() - 1));
+            assert!(context.would_overflow(alg.block_len()));
+            context.update(&vec![0u8; alg.block_len() - 1]);
+            assert!(!context.would_overflow(0));
+            assert!(context.would_overflow(1));
+            assert!(context.would_overflow(usize::MAX));
+            context.update(&[0]);
+            assert!(context.would_overflow(0));
+        }
+
         fn nearly_full_context(alg: &'static digest::Algorithm) -> digest::Context {
-            // All implementations currently support up to 2^64-1 bits
-            // of input; according to the spec, SHA-384 and SHA-512
-            // support up to 2^128-1, but that's not implemented yet.
-            let max_bytes = 1u64 << (64 - 3);
-            let max_blocks = max_bytes / u64_from_usize(alg.block_len());
-            let completed_bytes = (max_blocks - 1) * u64_from_usize(alg.block_len());
+            // SHA-1 and SHA-256 support up to 2^64-1 bits of input, and
+            // SHA-384 and SHA-512 support up to 2^128-1.
+            let max_bytes = 1u128 << (alg.len_len * 8 - 3);
+            let block_len = u128::from(u64_from_usize(alg.block_len()));
+            let max_blocks = max_bytes / block_len;
+            context_with_completed_bytes(alg, (max_blocks - 1) * block_len)
+        }
+
+        fn context_with_completed_bytes(
+            alg: &'static digest::Algorithm,
+            completed_bytes: u128,
+        ) -> digest::Context {
             digest::Context {
                 block: digest::BlockContext {
                     state: alg.initial_state.clone(),
@@ -810,9 +3802,126 @@ mod tests {
                 },
                 pending: [0u8; digest::MAX_BLOCK_LEN],
                 num_pending: 0,
+                sealed: false,
+                pending_cr: false,
+            }
+        }
+
+        #[test]
+        fn length_field_high_half() {
+            // 2^64 bytes is 2^67 bits, which sets bit 3 of the high half of
+            // the 128-bit length field.
+            for alg in [&digest::SHA384, &digest::SHA512, &digest::SHA512_256] {
+                let mut context = context_with_completed_bytes(alg, 1 << 64);
+                context.update(b"abc");
+                assert_eq!(context.pending_length_bits(), (1 << 67) + 24);
+
+                let final_blocks = context.final_blocks();
+                let len_field = &final_blocks[(final_blocks.len() - 16)..];
+                assert_eq!(len_field, &((1u128 << 67) + 24).to_be_bytes());
+
+                let mut block = context.block.clone();
+                let (completed, leftover) =
+                    block.block_data_order(&final_blocks, crate::cpu::features());
+                assert_eq!((completed, leftover.len()), (final_blocks.len(), 0));
+                let expected = (alg.format_output)(block.state.clone());
+                assert_eq!(context.finish().as_ref(), &expected.0[..alg.output_len()]);
             }
         }
 
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn serialize_past_2_to_the_64() {
+            use crate::digest::ContextData;
+
+            let completed_bytes = (1 << 64) + 128;
+            for alg in [&digest::SHA384, &digest::SHA512, &digest::SHA512_256] {
+                let mut context = context_with_completed_bytes(alg, completed_bytes);
+                context.update(b"abc");
+                let expected = context.clone().finish();
+
+                let data = ContextData::try_from(&context).unwrap();
+                assert_eq!(data.completed_bytes, completed_bytes);
+                let restored = digest::Context::deserialize(data.clone()).unwrap();
+                assert_eq!(restored.block.completed_bytes, completed_bytes);
+                assert_eq!(restored.finish().as_ref(), expected.as_ref());
+
+                let decoded = ContextData::from_bytes(&data.to_bytes()).unwrap();
+                assert_eq!(decoded.completed_bytes, completed_bytes);
+                let restored = digest::Context::deserialize(decoded).unwrap();
+                assert_eq!(restored.finish().as_ref(), expected.as_ref());
+
+                let mut buf = vec![0u8; context.serialized_len()];
+                assert_eq!(context.serialize_into(&mut buf), Ok(buf.len()));
+                assert_eq!(buf, data.to_bytes());
+
+                let stored = context.serialize_without_algorithm();
+                let restored = digest::Context::deserialize_with_algorithm(alg, &stored).unwrap();
+                assert_eq!(restored.finish().as_ref(), expected.as_ref());
+
+                #[cfg(feature = "serde")]
+                {
+                    let json = serde_json::to_string(&context).unwrap();
+                    let restored: digest::Context = serde_json::from_str(&json).unwrap();
+                    assert_eq!(restored.finish().as_ref(), expected.as_ref());
+                }
+
+                #[cfg(feature = "cbor")]
+                {
+                    let decoded = ContextData::from_cbor(&data.to_cbor()).unwrap();
+                    assert_eq!(decoded.completed_bytes, completed_bytes);
+                }
+            }
+        }
+
+        #[cfg(feature = "serialize")]
+        #[test]
+        fn deserialize_too_long() {
+            use crate::digest::{ContextData, DeserializeError};
+
+            // SHA-256 allows up to 2^64 - 1 bits, i.e. less than 2^61 bytes.
+            let alg = &digest::SHA256;
+            let ok =
+                ContextData::try_from(&context_with_completed_bytes(alg, (1 << 61) - 64)).unwrap();
+            assert!(digest::Context::deserialize(ok.clone()).is_ok());
+            assert!(digest::Context::with_state_and_pending(&ok, &[0; 63]).is_ok());
+
+            let context = context_with_completed_bytes(alg, 1 << 61);
+            let data = ContextData::try_from(&context).unwrap();
+            assert_eq!(
+                digest::Context::deserialize(data.clone()).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+            assert_eq!(
+                digest::Context::try_from(data.clone()).err(),
+                Some(DeserializeError::Malformed)
+            );
+            assert_eq!(
+                digest::Context::with_state_and_pending(&data, &[]).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+            assert_eq!(
+                ContextData::from_bytes(&data.to_bytes()).err(),
+                Some(DeserializeError::Malformed.into())
+            );
+            assert_eq!(
+                digest::Context::deserialize_with_algorithm(
+                    alg,
+                    &context.serialize_without_algorithm()
+                )
+                .err(),
+                Some(DeserializeError::Malformed.into())
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn length_field_high_half_sha256() {
+            let mut context = context_with_completed_bytes(&digest::SHA256, 1 << 64);
+            context.update(b"abc");
+            let _ = context.finish(); // should panic
+        }
+
         max_input_tests!(SHA1_FOR_LEGACY_USE_ONLY);
         max_input_tests!(SHA256);
         max_input_tests!(SHA384);
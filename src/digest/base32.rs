@@ -0,0 +1,124 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Unpadded, lowercase base32 ([RFC 4648] section 6) encoding.
+//!
+//! [RFC 4648]: https://www.rfc-editor.org/rfc/rfc4648#section-6
+
+use crate::error;
+
+static ALPHABET: [u8; 32] = *b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// The length of the unpadded base32 encoding of `len` bytes.
+pub(super) const fn encoded_len(len: usize) -> usize {
+    (len * 8 + 4) / 5
+}
+
+/// Writes the unpadded, lowercase base32 encoding of `bytes` into `out`.
+///
+/// `out` must be exactly `encoded_len(bytes.len())` bytes long.
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+pub(super) fn encode(bytes: &[u8], out: &mut [u8]) {
+    assert_eq!(out.len(), encoded_len(bytes.len()));
+
+    let mut acc = 0u16;
+    let mut bits = 0;
+    let mut out = out.iter_mut();
+    for &b in bytes {
+        acc = (acc << 8) | u16::from(b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            *out.next().unwrap() = ALPHABET[usize::from((acc >> bits) & 0x1f)];
+        }
+    }
+    if bits > 0 {
+        *out.next().unwrap() = ALPHABET[usize::from((acc << (5 - bits)) & 0x1f)];
+    }
+}
+
+/// Decodes the unpadded, lowercase base32 `input` into `out`.
+///
+/// Fails unless `input` is the canonical encoding of exactly `out.len()`
+/// bytes; in particular, unused trailing bits must be zero.
+pub(super) fn decode(input: &[u8], out: &mut [u8]) -> Result<(), error::Unspecified> {
+    if input.len() != encoded_len(out.len()) {
+        return Err(error::Unspecified);
+    }
+
+    let mut acc = 0u16;
+    let mut bits = 0;
+    let mut out = out.iter_mut();
+    for &c in input {
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(error::Unspecified)?;
+        // `value < 32`.
+        acc = (acc << 5) | u16::try_from(value).unwrap();
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            *out.next().unwrap() = (acc >> bits).to_be_bytes()[1];
+        }
+    }
+    if acc & ((1 << bits) - 1) != 0 {
+        return Err(error::Unspecified);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4648 section 10, lowercased and unpadded.
+    const VECTORS: &[(&[u8], &[u8])] = &[
+        (b"", b""),
+        (b"f", b"my"),
+        (b"fo", b"mzxq"),
+        (b"foo", b"mzxw6"),
+        (b"foob", b"mzxw6yq"),
+        (b"fooba", b"mzxw6ytb"),
+        (b"foobar", b"mzxw6ytboi"),
+    ];
+
+    #[test]
+    fn base32_rfc4648_vectors() {
+        for &(bytes, encoded) in VECTORS {
+            let mut out = [0u8; 16];
+            let out = &mut out[..encoded_len(bytes.len())];
+            encode(bytes, out);
+            assert_eq!(out, encoded);
+
+            let mut decoded = [0u8; 16];
+            let decoded = &mut decoded[..bytes.len()];
+            decode(encoded, decoded).unwrap();
+            assert_eq!(decoded, bytes);
+        }
+    }
+
+    #[test]
+    fn base32_decode_invalid() {
+        let mut out = [0u8; 1];
+        // Uppercase and padding aren't accepted.
+        assert!(decode(b"MY", &mut out).is_err());
+        assert!(decode(b"my======", &mut out).is_err());
+        // Nonzero trailing bits.
+        assert!(decode(b"mz", &mut out).is_err());
+        // Wrong length.
+        assert!(decode(b"m", &mut out).is_err());
+        assert!(decode(b"mzx", &mut out).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Digests of many independent messages.
+
+use super::{digest, sha256_x8, Algorithm, Digest, Output, MAX_OUTPUT_LEN, SHA256};
+use alloc::vec::Vec;
+
+/// The number of inputs hashed together by each `rayon` task.
+#[cfg(feature = "rayon")]
+const INPUTS_PER_TASK: usize = 64;
+
+/// Computes the digest of each of `inputs`.
+///
+/// `result[i]` is always `digest(algorithm, inputs[i])`. For SHA-256, each
+/// run of eight consecutive 64-byte inputs is hashed with [`sha256_x8`],
+/// which hashes them together on CPUs that support it; all other inputs are
+/// hashed one after another. With the `rayon` feature, the inputs are split
+/// into groups that are hashed in parallel.
+///
+/// See also `digest_auto`, available with the `std` feature, which uses
+/// scoped threads instead of `rayon`.
+pub fn digest_batch(algorithm: &'static Algorithm, inputs: &[&[u8]]) -> Vec<Digest> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        inputs
+            .par_chunks(INPUTS_PER_TASK)
+            .flat_map_iter(|inputs| digest_sequential(algorithm, inputs))
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        digest_sequential(algorithm, inputs)
+    }
+}
+
+fn digest_sequential(algorithm: &'static Algorithm, inputs: &[&[u8]]) -> Vec<Digest> {
+    let mut out = Vec::with_capacity(inputs.len());
+    let mut remaining = inputs;
+    while let Some((first, rest)) = remaining.split_first() {
+        if algorithm == &SHA256 {
+            if let Some(blocks) = eight_blocks(remaining) {
+                out.extend(sha256_x8(&blocks).iter().map(|value| {
+                    let mut output = Output([0u8; MAX_OUTPUT_LEN]);
+                    output.0[..value.len()].copy_from_slice(value);
+                    Digest {
+                        value: output,
+                        algorithm: &SHA256,
+                    }
+                }));
+                remaining = &remaining[8..];
+                continue;
+            }
+        }
+        out.push(digest(algorithm, first));
+        remaining = rest;
+    }
+    out
+}
+
+/// The first eight inputs, if there are eight and all of them are 64 bytes
+/// long.
+fn eight_blocks(inputs: &[&[u8]]) -> Option<[[u8; 64]; 8]> {
+    let inputs = inputs.get(..8)?;
+    let mut blocks = [[0u8; 64]; 8];
+    for (block, input) in blocks.iter_mut().zip(inputs) {
+        *block = (*input).try_into().ok()?;
+    }
+    Some(blocks)
+}
@@ -0,0 +1,254 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! The allocating, self-describing form of the binary encoding described in
+//! the `encoding` module, the form without the algorithm, and decoding them.
+
+use super::{
+    encoding::{algorithm_tag, COMPLETED_BYTES_LEN, FIXED_LEN, NUM_PENDING_LEN, STATE_LEN},
+    sha2, Algorithm, AlgorithmId, BlockContext, Context, ContextData, DigestResult, MAX_BLOCK_LEN,
+};
+use crate::polyfill::slice;
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "std")]
+extern crate std;
+
+const COMPLETED_BYTES_LEN_V1: usize = 8;
+
+impl Context {
+    /// Serializes the state of the context without recording its algorithm.
+    ///
+    /// This is more compact than the self-describing forms, and is intended
+    /// for when many checkpoints are stored for the same algorithm, which is
+    /// known from elsewhere. Use [`Self::deserialize_with_algorithm`] to
+    /// restore the context.
+    pub fn serialize_without_algorithm(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(FIXED_LEN + self.num_pending);
+        out.extend_from_slice(&self.block.completed_bytes.to_be_bytes());
+        // `num_pending < MAX_BLOCK_LEN`, which fits in a `u16`.
+        let num_pending = u16::try_from(self.num_pending).unwrap();
+        out.extend_from_slice(&num_pending.to_be_bytes());
+        self.block
+            .state
+            .to_u64_words()
+            .iter()
+            .for_each(|w| out.extend_from_slice(&w.to_be_bytes()));
+        out.extend_from_slice(&self.pending[..self.num_pending]);
+        out
+    }
+
+    /// Restores a context that was serialized with
+    /// [`Self::serialize_without_algorithm`].
+    ///
+    /// `algorithm` must be the algorithm of the serialized context. Fails if
+    /// `input` isn't a valid serialized context for `algorithm`.
+    pub fn deserialize_with_algorithm(
+        algorithm: &'static Algorithm,
+        input: &[u8],
+    ) -> DigestResult<Self> {
+        Ok(Self::deserialize_encoding(
+            algorithm,
+            input,
+            COMPLETED_BYTES_LEN,
+        )?)
+    }
+
+    /// Like [`Self::deserialize_with_algorithm`], with `completed_bytes`
+    /// stored in `completed_bytes_len` bytes, which is 8 in version 1 of
+    /// the encoding.
+    fn deserialize_encoding(
+        algorithm: &'static Algorithm,
+        input: &[u8],
+        completed_bytes_len: usize,
+    ) -> Result<Self, DeserializeError> {
+        let fixed_len = completed_bytes_len + NUM_PENDING_LEN + STATE_LEN;
+        let (fixed, pending) =
+            slice::split_at_checked(input, fixed_len).ok_or(DeserializeError::Malformed)?;
+        let (completed_bytes, rest) = fixed.split_at(completed_bytes_len);
+        let (num_pending, state) = rest.split_at(NUM_PENDING_LEN);
+
+        let mut be_bytes = [0u8; COMPLETED_BYTES_LEN];
+        be_bytes[(COMPLETED_BYTES_LEN - completed_bytes_len)..].copy_from_slice(completed_bytes);
+        let completed_bytes = u128::from_be_bytes(be_bytes);
+        let num_pending = usize::from(u16::from_be_bytes(num_pending.try_into().unwrap()));
+        if num_pending != pending.len() || num_pending >= MAX_BLOCK_LEN {
+            return Err(DeserializeError::Malformed);
+        }
+
+        let mut words = [0u64; sha2::CHAINING_WORDS];
+        for (w, bytes) in words.iter_mut().zip(state.chunks_exact(8)) {
+            *w = u64::from_be_bytes(bytes.try_into().unwrap());
+        }
+
+        let mut block = BlockContext::new(algorithm);
+        block.state = block.state.with_u64_words(words).map_err(|index| {
+            DeserializeError::StateWordTooLarge {
+                word: words[index],
+                index,
+            }
+        })?;
+        block.completed_bytes = completed_bytes;
+
+        let mut ctx = Self {
+            block,
+            pending: [0u8; MAX_BLOCK_LEN],
+            num_pending,
+            sealed: false,
+            pending_cr: false,
+        };
+        ctx.pending[..num_pending].copy_from_slice(pending);
+        ctx.debug_check_invariants()
+            .map_err(|_| DeserializeError::Malformed)?;
+        Ok(ctx)
+    }
+}
+
+// The length of `completed_bytes` in version `version` of the encoding.
+fn completed_bytes_len(version: u32) -> usize {
+    match version {
+        1 => COMPLETED_BYTES_LEN_V1,
+        _ => COMPLETED_BYTES_LEN,
+    }
+}
+
+impl ContextData {
+    /// Encodes `self` in a compact, self-describing binary form that
+    /// doesn't require serde.
+    ///
+    /// `self` should have been constructed from a `Context`; fields that
+    /// are inconsistent with each other are encoded as-is, and
+    /// [`Self::from_bytes`] will reject the result.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let pending = self
+            .pending
+            .get(..self.num_pending)
+            .unwrap_or(&self.pending);
+        let mut out = Vec::with_capacity(2 + FIXED_LEN + pending.len());
+        out.push(u8::try_from(self.version).unwrap_or(u8::MAX));
+        out.push(algorithm_tag(self.algorithm));
+        let completed_bytes = self.completed_bytes.to_be_bytes();
+        out.extend_from_slice(
+            &completed_bytes[(COMPLETED_BYTES_LEN - completed_bytes_len(self.version))..],
+        );
+        let num_pending = u16::try_from(self.num_pending).unwrap_or(u16::MAX);
+        out.extend_from_slice(&num_pending.to_be_bytes());
+        self.state_data
+            .iter()
+            .for_each(|w| out.extend_from_slice(&w.to_be_bytes()));
+        out.extend_from_slice(pending);
+        out
+    }
+
+    /// Decodes a `ContextData` previously encoded with [`Self::to_bytes`].
+    ///
+    /// Fails with `DeserializeError::Malformed` if `input` is truncated or
+    /// otherwise isn't a valid encoding, including if it's for a custom
+    /// algorithm, which earlier versions encoded but which can't be
+    /// restored, and with `DeserializeError::UnsupportedVersion` if it was
+    /// encoded by a version of the crate that uses a different
+    /// representation.
+    pub fn from_bytes(input: &[u8]) -> DigestResult<Self> {
+        let (header, context) =
+            slice::split_at_checked(input, 2).ok_or(DeserializeError::Malformed)?;
+        let version = u32::from(header[0]);
+        if !Self::is_supported_version(version) {
+            return Err(DeserializeError::UnsupportedVersion(version).into());
+        }
+        let id = AlgorithmId::ALL
+            .into_iter()
+            .find(|&id| algorithm_tag(id) == header[1])
+            .ok_or(DeserializeError::Malformed)?;
+        let algorithm = id.algorithm();
+        let ctx = Context::deserialize_encoding(algorithm, context, completed_bytes_len(version))?;
+        Ok(Self::try_from(&ctx)?)
+    }
+}
+
+/// An error indicating that serialized context state is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The serialized state is malformed or inconsistent with the algorithm.
+    Malformed,
+
+    /// The state word at `index` doesn't fit in the algorithm's word size.
+    StateWordTooLarge {
+        /// The value of the state word.
+        word: u64,
+        /// The index of the state word.
+        index: usize,
+    },
+
+    /// The algorithm name isn't one this version recognizes.
+    UnknownAlgorithm(String),
+
+    /// The pending buffer has the wrong length.
+    BadPendingLength {
+        /// The required length.
+        expected: usize,
+        /// The actual length.
+        actual: usize,
+    },
+
+    /// The state has the wrong number of words.
+    BadStateLength {
+        /// The required number of words.
+        expected: usize,
+        /// The actual number of words.
+        actual: usize,
+    },
+
+    /// The number of pending bytes isn't less than the block length.
+    InvalidNumPending,
+
+    /// The state's word width doesn't match the algorithm's: 32 bits for
+    /// SHA-1 and SHA-256, 64 bits for the SHA-512 family.
+    StateWidthMismatch,
+
+    /// The representation has a version that this version of the crate
+    /// doesn't support.
+    UnsupportedVersion(u32),
+}
+
+impl core::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Malformed => f.write_str("Malformed"),
+            Self::StateWordTooLarge { word, index } => {
+                write!(
+                    f,
+                    "StateWordTooLarge {{ word: {:#x}, index: {} }}",
+                    word, index
+                )
+            }
+            Self::UnknownAlgorithm(name) => write!(f, "UnknownAlgorithm({:?})", name),
+            Self::BadPendingLength { expected, actual } => write!(
+                f,
+                "BadPendingLength {{ expected: {}, actual: {} }}",
+                expected, actual
+            ),
+            Self::BadStateLength { expected, actual } => write!(
+                f,
+                "BadStateLength {{ expected: {}, actual: {} }}",
+                expected, actual
+            ),
+            Self::InvalidNumPending => f.write_str("InvalidNumPending"),
+            Self::StateWidthMismatch => f.write_str("StateWidthMismatch"),
+            Self::UnsupportedVersion(version) => write!(f, "UnsupportedVersion({})", version),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeserializeError {}
@@ -0,0 +1,137 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{
+    dynstate::{self, DynState},
+    sha2, Algorithm, BlockContext, Identity, OutputLen, SHA256_OUTPUT_LEN, SHA512_LEN_LEN,
+    SHA512_OUTPUT_LEN,
+};
+use crate::error;
+use core::num::Wrapping;
+
+/// Builds an [`Algorithm`] that uses the SHA-256 or SHA-512 compression
+/// function with a custom initial state and output length.
+///
+/// This is intended for research and for experimenting with nonstandard
+/// SHA-2 variants. An algorithm with a custom initial state is **not**
+/// SHA-256 or SHA-512, has had no cryptanalysis of its own, and won't
+/// interoperate with anything that doesn't use the same initial state.
+/// Standard variants like SHA-512/256 are already provided as statics and
+/// should be used instead.
+///
+/// Since [`super::Context::new`] takes a `&'static Algorithm`, the built
+/// algorithm must be stored somewhere that lives for the rest of the
+/// program, e.g. in a `static` initialized on first use, or by leaking it.
+///
+/// A built algorithm is only equal to itself, not to the algorithm it is
+/// based on nor to any other built algorithm, even one with the same
+/// parameters. Contexts using a built algorithm can be serialized, but
+/// they can only be restored with
+/// [`super::Context::deserialize_with_algorithm`].
+///
+/// # Examples
+///
+/// ```
+/// use ring::digest;
+///
+/// // SHA-256 with the first eight primes as its initial state.
+/// let algorithm = digest::AlgorithmBuilder::sha256([2, 3, 5, 7, 11, 13, 17, 19])
+///     .build()
+///     .unwrap();
+/// let algorithm: &'static digest::Algorithm = Box::leak(Box::new(algorithm));
+///
+/// let custom = digest::digest(algorithm, b"hello");
+/// assert_ne!(custom.as_ref(), digest::digest(&digest::SHA256, b"hello").as_ref());
+/// ```
+#[derive(Clone)]
+pub struct AlgorithmBuilder {
+    initial_state: DynState,
+    output_len: usize,
+}
+
+impl AlgorithmBuilder {
+    /// Starts building an algorithm based on the SHA-256 compression
+    /// function with the initial state `initial_state`.
+    ///
+    /// The output length defaults to 32 bytes.
+    pub fn sha256(initial_state: [u32; sha2::CHAINING_WORDS]) -> Self {
+        Self {
+            initial_state: DynState::new32(initial_state.map(Wrapping)),
+            output_len: SHA256_OUTPUT_LEN,
+        }
+    }
+
+    /// Starts building an algorithm based on the SHA-512 compression
+    /// function with the initial state `initial_state`.
+    ///
+    /// The output length defaults to 64 bytes.
+    pub fn sha512(initial_state: [u64; sha2::CHAINING_WORDS]) -> Self {
+        Self {
+            initial_state: DynState::new64(initial_state.map(Wrapping)),
+            output_len: SHA512_OUTPUT_LEN,
+        }
+    }
+
+    /// Sets the length of the output, in bytes.
+    ///
+    /// The output is the first `output_len` bytes of the final chaining
+    /// state. It must be 20, 32, 48, or 64 bytes, and no longer than the
+    /// chaining state: 32 bytes for SHA-256 and 64 bytes for SHA-512.
+    pub fn output_len(mut self, output_len: usize) -> Self {
+        self.output_len = output_len;
+        self
+    }
+
+    /// Builds the algorithm.
+    ///
+    /// Fails if the output length isn't valid for the compression function.
+    pub fn build(self) -> Result<Algorithm, error::Unspecified> {
+        let output_len = match self.output_len {
+            20 => OutputLen::_160,
+            32 => OutputLen::_256,
+            48 => OutputLen::_384,
+            64 => OutputLen::_512,
+            _ => return Err(error::Unspecified),
+        };
+
+        let algorithm = match self.initial_state {
+            DynState::As32(_) => Algorithm {
+                output_len,
+                chaining_len: SHA256_OUTPUT_LEN,
+                block_len: sha2::SHA256_BLOCK_LEN,
+                len_len: 64 / 8,
+                block_data_order: dynstate::sha256_block_data_order,
+                format_output: dynstate::sha256_format_output,
+                finalize: BlockContext::finish_md,
+                initial_state: self.initial_state,
+                id: Identity::CustomSha256,
+            },
+            DynState::As64(_) => Algorithm {
+                output_len,
+                chaining_len: SHA512_OUTPUT_LEN,
+                block_len: sha2::SHA512_BLOCK_LEN,
+                len_len: SHA512_LEN_LEN,
+                block_data_order: dynstate::sha512_block_data_order,
+                format_output: dynstate::sha512_format_output,
+                finalize: BlockContext::finish_md,
+                initial_state: self.initial_state,
+                id: Identity::CustomSha512,
+            },
+        };
+        if algorithm.output_len() > algorithm.chaining_len {
+            return Err(error::Unspecified);
+        }
+        Ok(algorithm)
+    }
+}
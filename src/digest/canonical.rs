@@ -0,0 +1,135 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Canonical, length-framed hashing of structured values.
+
+use super::{Algorithm, Context, Digest};
+use crate::polyfill;
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+/// A digest context that frames each field it absorbs with its length, so
+/// that different sequences of fields never hash the same.
+///
+/// Each field is absorbed as its length in bytes, as a 64-bit big-endian
+/// integer, followed by its bytes. Without framing, the fields `"ab", "c"`
+/// and `"a", "bc"` would be indistinguishable.
+#[derive(Clone)]
+pub struct CanonicalHasher {
+    ctx: Context,
+}
+
+impl CanonicalHasher {
+    /// Constructs a new hasher.
+    pub fn new(algorithm: &'static Algorithm) -> Self {
+        Self {
+            ctx: Context::new(algorithm),
+        }
+    }
+
+    /// Absorbs `field`, preceded by its length.
+    pub fn write_field(&mut self, field: &[u8]) {
+        self.ctx
+            .update(&polyfill::u64_from_usize(field.len()).to_be_bytes());
+        self.ctx.update(field);
+    }
+
+    /// Finalizes the digest calculation and returns the digest value.
+    pub fn finish(self) -> Digest {
+        self.ctx.finish()
+    }
+
+    /// The algorithm that this hasher is using.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.ctx.algorithm()
+    }
+}
+
+/// A value that can be hashed canonically with a [`CanonicalHasher`].
+///
+/// Byte strings and strings are absorbed as one field. Integers are absorbed
+/// as one field containing their big-endian encoding, and `bool` as one
+/// byte field, 0 or 1. Types composed of other values, like those deriving
+/// this trait with the `derive` feature, absorb each of their parts in turn.
+pub trait CanonicalHash {
+    /// Absorbs `self` into `hasher`.
+    fn hash_fields(&self, hasher: &mut CanonicalHasher);
+
+    /// Returns the canonical digest of `self` using `algorithm`.
+    fn canonical_hash(&self, algorithm: &'static Algorithm) -> Digest {
+        let mut hasher = CanonicalHasher::new(algorithm);
+        self.hash_fields(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<T: CanonicalHash + ?Sized> CanonicalHash for &T {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        (**self).hash_fields(hasher)
+    }
+}
+
+impl CanonicalHash for [u8] {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        hasher.write_field(self)
+    }
+}
+
+impl<const N: usize> CanonicalHash for [u8; N] {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        hasher.write_field(self)
+    }
+}
+
+impl CanonicalHash for str {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        hasher.write_field(self.as_bytes())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl CanonicalHash for Vec<u8> {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        hasher.write_field(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl CanonicalHash for String {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        hasher.write_field(self.as_bytes())
+    }
+}
+
+impl CanonicalHash for bool {
+    fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+        hasher.write_field(&[u8::from(*self)])
+    }
+}
+
+macro_rules! impl_canonical_hash_for_int {
+    ( $( $t:ty ),* ) => {
+        $(
+            impl CanonicalHash for $t {
+                fn hash_fields(&self, hasher: &mut CanonicalHasher) {
+                    hasher.write_field(&self.to_be_bytes())
+                }
+            }
+        )*
+    };
+}
+
+impl_canonical_hash_for_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
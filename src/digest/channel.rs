@@ -0,0 +1,35 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Hashing data that is streamed through a channel.
+
+use super::{Algorithm, Context, Digest};
+use alloc::vec::Vec;
+
+extern crate std;
+
+/// Hashes the chunks received from `rx`, in order, until every sender has
+/// disconnected, and returns the digest of their concatenation.
+///
+/// This lets a producer thread stream data to a dedicated hashing thread.
+/// It blocks while the channel is empty; chunks may be any length, including
+/// empty.
+pub fn digest_receiver(
+    algorithm: &'static Algorithm,
+    rx: std::sync::mpsc::Receiver<Vec<u8>>,
+) -> Digest {
+    let mut ctx = Context::new(algorithm);
+    rx.iter().for_each(|chunk| ctx.update(&chunk));
+    ctx.finish()
+}
@@ -0,0 +1,123 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Non-cryptographic CRC-32 checksums.
+
+use crate::{cpu, polyfill::slice};
+
+/// Returns the CRC-32 (IEEE 802.3) checksum of `data`.
+///
+/// This is **not** a cryptographic digest; it only detects accidental
+/// corruption. It is the checksum used by zlib, gzip, PNG, and Ethernet.
+pub fn crc32(data: &[u8]) -> u32 {
+    !update_sw(&CRC32_TABLE, !0, data)
+}
+
+/// Returns the CRC-32C (Castagnoli) checksum of `data`.
+///
+/// This is **not** a cryptographic digest; it only detects accidental
+/// corruption. It is the checksum used by iSCSI, ext4, and SCTP. On x86-64
+/// the SSE4.2 `crc32` instruction is used when it is available.
+pub fn crc32c(data: &[u8]) -> u32 {
+    let cpu_features = cpu::features();
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::SSE42.available(cpu_features) {
+            // SAFETY: We just verified that SSE4.2 is available.
+            return !unsafe { crc32c_update_sse42(!0, data) };
+        }
+    }
+    let _ = cpu_features;
+
+    !update_sw(&CRC32C_TABLE, !0, data)
+}
+
+// Reflected polynomials.
+const CRC32_POLY: u32 = 0xedb8_8320;
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+static CRC32_TABLE: [u32; 256] = make_table(CRC32_POLY);
+static CRC32C_TABLE: [u32; 256] = make_table(CRC32C_POLY);
+
+const fn make_table(poly: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < table.len() {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn update_sw(table: &[u32; 256], crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &b| {
+        table[usize::from(crc.to_le_bytes()[0] ^ b)] ^ (crc >> 8)
+    })
+}
+
+// The intrinsics are safe to call from a `target_feature` function only as of
+// Rust 1.87, so the `unsafe` blocks are needed for older versions.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.2")]
+#[allow(unused_unsafe)]
+unsafe fn crc32c_update_sse42(crc: u32, data: &[u8]) -> u32 {
+    use core::arch::x86_64::{_mm_crc32_u64, _mm_crc32_u8};
+
+    let (words, rest) = slice::as_chunks::<u8, 8>(data);
+    let mut crc = u64::from(crc);
+    for word in words {
+        crc = unsafe { _mm_crc32_u64(crc, u64::from_le_bytes(*word)) };
+    }
+    // The CRC-32C instructions only ever produce 32-bit values.
+    #[allow(clippy::cast_possible_truncation)]
+    let mut crc = crc as u32;
+    for &b in rest {
+        crc = unsafe { _mm_crc32_u8(crc, b) };
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn crc32c_sse42_matches_software() {
+        if !cpu::intel::SSE42.available(cpu::features()) {
+            return;
+        }
+        let data: [u8; 300] = core::array::from_fn(|i| (i * 7 + 3).to_le_bytes()[0]);
+        for start in 0..9 {
+            for end in start..data.len() {
+                let data = &data[start..end];
+                let sw = update_sw(&CRC32C_TABLE, !0, data);
+                let hw = unsafe { crc32c_update_sse42(!0, data) };
+                assert_eq!(sw, hw);
+            }
+        }
+    }
+}
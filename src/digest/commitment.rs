@@ -0,0 +1,98 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Salted hash commitments.
+
+use super::{Algorithm, Context, Digest};
+use crate::{error, rand};
+
+/// The length of the random salt of an [`Opening`], in bytes.
+pub const COMMITMENT_SALT_LEN: usize = 32;
+
+/// A commitment to some data, produced by [`commit`].
+///
+/// The commitment is the digest of a random salt followed by the data, so
+/// it reveals nothing about the data until the [`Opening`] is revealed.
+/// Use [`Self::as_ref`] to get the value to publish.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Commitment(Digest);
+
+impl From<Digest> for Commitment {
+    fn from(digest: Digest) -> Self {
+        Self(digest)
+    }
+}
+
+impl AsRef<[u8]> for Commitment {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+/// The salt that opens a [`Commitment`].
+///
+/// This must be kept secret until the committed data is revealed.
+#[derive(Clone)]
+pub struct Opening {
+    salt: [u8; COMMITMENT_SALT_LEN],
+}
+
+impl Opening {
+    /// Constructs an opening from a revealed salt.
+    pub fn new(salt: [u8; COMMITMENT_SALT_LEN]) -> Self {
+        Self { salt }
+    }
+
+    /// The salt.
+    pub fn salt(&self) -> &[u8; COMMITMENT_SALT_LEN] {
+        &self.salt
+    }
+}
+
+/// Commits to `data` with a random salt generated from `rng`.
+///
+/// Returns the commitment, which can be published immediately, and the
+/// opening, which is revealed along with `data` to let others check the
+/// commitment with [`verify_commitment`].
+pub fn commit(
+    algorithm: &'static Algorithm,
+    data: &[u8],
+    rng: &dyn rand::SecureRandom,
+) -> Result<(Commitment, Opening), error::Unspecified> {
+    let mut salt = [0u8; COMMITMENT_SALT_LEN];
+    rng.fill(&mut salt)?;
+    let opening = Opening::new(salt);
+    Ok((commitment(algorithm, data, &opening), opening))
+}
+
+/// Returns whether `commitment` is a commitment to `data` with `opening`,
+/// made with `algorithm`.
+///
+/// The commitment is compared in constant time.
+pub fn verify_commitment(
+    algorithm: &'static Algorithm,
+    commitment: &Commitment,
+    data: &[u8],
+    opening: &Opening,
+) -> bool {
+    self::commitment(algorithm, data, opening) == *commitment
+}
+
+fn commitment(algorithm: &'static Algorithm, data: &[u8], opening: &Opening) -> Commitment {
+    let mut ctx = Context::new(algorithm);
+    ctx.update(&opening.salt);
+    ctx.update(data);
+    Commitment(ctx.finish())
+}
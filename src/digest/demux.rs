@@ -0,0 +1,63 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Per-stream hashing of interleaved, multiplexed streams.
+
+use super::{Algorithm, Context, Digest};
+use std::collections::HashMap;
+
+extern crate std;
+
+/// Computes a separate digest for each stream of a multiplexed input.
+///
+/// Records from different streams may be fed in any interleaving; each
+/// stream's digest is of its own records, concatenated in the order they
+/// were fed. A stream's context is created the first time its id is fed.
+#[derive(Clone)]
+pub struct DemuxHasher {
+    algorithm: &'static Algorithm,
+    streams: HashMap<u32, Context>,
+}
+
+impl DemuxHasher {
+    /// Constructs a new hasher that will use `algorithm` for every stream.
+    pub fn new(algorithm: &'static Algorithm) -> Self {
+        Self {
+            algorithm,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Updates the digest of stream `stream_id` with `data`.
+    pub fn feed(&mut self, stream_id: u32, data: &[u8]) {
+        let algorithm = self.algorithm;
+        self.streams
+            .entry(stream_id)
+            .or_insert_with(|| Context::new(algorithm))
+            .update(data);
+    }
+
+    /// Finalizes the digest of every stream that was fed.
+    pub fn finish(self) -> HashMap<u32, Digest> {
+        self.streams
+            .into_iter()
+            .map(|(stream_id, ctx)| (stream_id, ctx.finish()))
+            .collect()
+    }
+
+    /// The algorithm that this hasher is using.
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+}
@@ -15,6 +15,8 @@
 use super::{format_output, sha1, sha2, Output};
 use crate::{cpu, polyfill::slice};
 
+use core::num::Wrapping;
+
 // Invariant: When constructed with `new32` (resp. `new64`), `As32` (resp.
 // `As64`) is the active variant.
 // Invariant: The active variant never changes after initialization.
@@ -24,6 +26,16 @@ pub(super) enum DynState {
     As32(sha2::State32),
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for DynState {
+    fn zeroize(&mut self) {
+        match self {
+            Self::As64(state) => state.zeroize(),
+            Self::As32(state) => state.zeroize(),
+        }
+    }
+}
+
 impl DynState {
     pub const fn new32(initial_state: sha2::State32) -> Self {
         Self::As32(initial_state)
@@ -32,12 +44,68 @@ impl DynState {
     pub const fn new64(initial_state: sha2::State64) -> Self {
         Self::As64(initial_state)
     }
+
+    /// The state words, each zero-extended to 64 bits if necessary.
+    pub fn to_u64_words(&self) -> [u64; sha2::CHAINING_WORDS] {
+        match self {
+            Self::As64(state) => state.map(|Wrapping(w)| w),
+            Self::As32(state) => state.map(|Wrapping(w)| u64::from(w)),
+        }
+    }
+
+    /// Constructs a state with the same active variant as `self` from `words`.
+    ///
+    /// Fails with the index of the first word that doesn't fit in the word
+    /// size of the active variant, if any.
+    #[cfg(feature = "serialize")]
+    pub fn with_u64_words(&self, words: [u64; sha2::CHAINING_WORDS]) -> Result<Self, usize> {
+        match self {
+            Self::As64(_) => Ok(Self::As64(words.map(Wrapping))),
+            Self::As32(_) => {
+                let mut state = [Wrapping(0); sha2::CHAINING_WORDS];
+                for (index, (s, w)) in state.iter_mut().zip(words).enumerate() {
+                    *s = Wrapping(u32::try_from(w).map_err(|_| index)?);
+                }
+                Ok(Self::As32(state))
+            }
+        }
+    }
+}
+
+impl DynState {
+    /// Constructs a state with the same active variant as `self` whose
+    /// leading words are decoded, big-endian, from `bytes`; the inverse of
+    /// the algorithms' `format_output`. The remaining words are kept from
+    /// `self`.
+    ///
+    /// `bytes` must be a whole number of words, and no more than
+    /// `sha2::CHAINING_WORDS` of them.
+    pub fn with_be_bytes(&self, bytes: &[u8]) -> Self {
+        let mut state = self.clone();
+        match &mut state {
+            Self::As64(state) => {
+                assert_eq!(bytes.len() % 8, 0);
+                state
+                    .iter_mut()
+                    .zip(bytes.chunks_exact(8))
+                    .for_each(|(s, b)| s.0 = u64::from_be_bytes(b.try_into().unwrap()));
+            }
+            Self::As32(state) => {
+                assert_eq!(bytes.len() % 4, 0);
+                state
+                    .iter_mut()
+                    .zip(bytes.chunks_exact(4))
+                    .for_each(|(s, b)| s.0 = u32::from_be_bytes(b.try_into().unwrap()));
+            }
+        }
+        state
+    }
 }
 
 pub(super) fn sha1_block_data_order<'d>(
     state: &mut DynState,
     data: &'d [u8],
-    _cpu_features: cpu::Features,
+    cpu_features: cpu::Features,
 ) -> (usize, &'d [u8]) {
     let state = match state {
         DynState::As32(state) => state,
@@ -47,7 +115,7 @@ pub(super) fn sha1_block_data_order<'d>(
     };
 
     let (full_blocks, leftover) = slice::as_chunks(data);
-    sha1::sha1_block_data_order(state, full_blocks);
+    sha1::sha1_block_data_order(state, full_blocks, cpu_features);
     (full_blocks.len() * sha1::BLOCK_LEN.into(), leftover)
 }
 
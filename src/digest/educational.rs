@@ -0,0 +1,49 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Single-block SHA-256 tracing for educational tooling.
+
+use super::sha2;
+use core::num::Wrapping;
+
+/// The intermediate values of one SHA-256 block compression.
+///
+/// Returned by [`trace_block256`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockTrace {
+    /// The expanded message schedule W<sub>0</sub>..W<sub>63</sub>.
+    pub schedule: [u32; 64],
+
+    /// The working variables `[a, b, c, d, e, f, g, h]` after each of the 64
+    /// rounds.
+    pub rounds: [[u32; 8]; 64],
+
+    /// The chaining state after the block has been added in.
+    pub state: [u32; 8],
+}
+
+/// Compresses a single SHA-256 `block` into `state`, recording the message
+/// schedule and the working variables after every round.
+///
+/// `block` is compressed as-is; no padding is applied. This is intended for
+/// learning and visualization tools and is much slower than
+/// [`super::Context`]; it is never used to compute digests.
+pub fn trace_block256(state: &[u32; 8], block: &[u8; 64]) -> BlockTrace {
+    let (schedule, rounds, out) = sha2::trace_block_32(state.map(Wrapping), block);
+    BlockTrace {
+        schedule: schedule.map(|w| w.0),
+        rounds: rounds.map(|round| round.map(|w| w.0)),
+        state: out.map(|w| w.0),
+    }
+}
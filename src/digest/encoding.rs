@@ -0,0 +1,105 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! A compact binary encoding of a `Context`'s state.
+//!
+//! The encoding of a context, without any information about its algorithm,
+//! is:
+//!
+//! ```text
+//! completed_bytes:  16 bytes, big-endian
+//! num_pending:      2 bytes, big-endian
+//! state:            8 words of 8 bytes each, big-endian
+//! pending:          `num_pending` bytes
+//! ```
+//!
+//! For algorithms with 32-bit state words, each word is zero-extended to 64
+//! bits. Version 1 of the encoding stored `completed_bytes` in 8 bytes;
+//! `ContextData::from_bytes` still accepts it.
+//!
+//! `Context::serialize_into` and `ContextData::to_bytes` prefix that
+//! encoding with a version and the algorithm, so that it is
+//! self-describing:
+//!
+//! ```text
+//! version:          1 byte, `ContextData::version`
+//! algorithm:        1 byte, see `algorithm_tag`
+//! context:          as above
+//! ```
+
+use super::{sha2, AlgorithmId, BufferTooSmall, Context, DigestResult};
+
+/// The version of the encoding, and of `ContextData`, that this version of
+/// the crate writes.
+pub(super) const CURRENT_VERSION: u32 = 2;
+
+pub(super) const COMPLETED_BYTES_LEN: usize = 16;
+pub(super) const NUM_PENDING_LEN: usize = 2;
+pub(super) const STATE_LEN: usize = sha2::CHAINING_WORDS * 8;
+pub(super) const FIXED_LEN: usize = COMPLETED_BYTES_LEN + NUM_PENDING_LEN + STATE_LEN;
+
+impl Context {
+    /// The length of the encoding written by [`Self::serialize_into`].
+    pub fn serialized_len(&self) -> usize {
+        2 + FIXED_LEN + self.num_pending
+    }
+
+    /// Writes the encoding that `ContextData::to_bytes` produces for this
+    /// context to the start of `buf`, without allocating, and returns its
+    /// length. This is available without the `serialize` feature.
+    ///
+    /// Fails if `buf` is shorter than [`Self::serialized_len`] or if the
+    /// algorithm is a custom one, in which case `buf` is left unchanged.
+    /// Restore the context with `ContextData::from_bytes` and
+    /// `Context::deserialize`.
+    pub fn serialize_into(&self, buf: &mut [u8]) -> DigestResult<usize> {
+        let id = self.block.algorithm.algorithm_id()?;
+        let len = self.serialized_len();
+        let out = buf.get_mut(..len).ok_or(BufferTooSmall)?;
+        let (header, out) = out.split_at_mut(2);
+        header[0] = u8::try_from(CURRENT_VERSION).unwrap();
+        header[1] = algorithm_tag(id);
+
+        let (completed_bytes, out) = out.split_at_mut(COMPLETED_BYTES_LEN);
+        completed_bytes.copy_from_slice(&self.block.completed_bytes.to_be_bytes());
+
+        let (num_pending, out) = out.split_at_mut(NUM_PENDING_LEN);
+        // `num_pending < MAX_BLOCK_LEN`, which fits in a `u16`.
+        let value = u16::try_from(self.num_pending).unwrap();
+        num_pending.copy_from_slice(&value.to_be_bytes());
+
+        let (state, pending) = out.split_at_mut(STATE_LEN);
+        for (bytes, w) in state
+            .chunks_exact_mut(8)
+            .zip(self.block.state.to_u64_words())
+        {
+            bytes.copy_from_slice(&w.to_be_bytes());
+        }
+        pending.copy_from_slice(&self.pending[..self.num_pending]);
+        Ok(len)
+    }
+}
+
+// These values are part of the encoding and must never change. 6 and 7 were
+// used for custom algorithms, which can no longer be serialized.
+pub(super) fn algorithm_tag(id: AlgorithmId) -> u8 {
+    match id {
+        AlgorithmId::SHA1 => 1,
+        AlgorithmId::SHA256 => 2,
+        AlgorithmId::SHA384 => 3,
+        AlgorithmId::SHA512 => 4,
+        AlgorithmId::SHA512_256 => 5,
+        AlgorithmId::SHA512_224 => 8,
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Algorithm, Context};
+use core::hash::{BuildHasher, Hasher};
+
+/// A [`BuildHasher`] whose hashers are backed by a digest algorithm.
+///
+/// **This is not a secure or fast hasher.** Each `Hasher::finish` computes
+/// a full digest and then throws away all but 64 bits of it, so it is much
+/// slower than the hashers that `std` uses, and the truncated result has
+/// none of the collision resistance of the underlying algorithm. There is
+/// no per-map random key either, so it offers no protection against
+/// hash-flooding. Use it for testing hash distribution, not in production
+/// maps.
+///
+/// # Examples
+///
+/// ```
+/// use ring::digest;
+/// use std::collections::HashMap;
+///
+/// let mut map = HashMap::with_hasher(digest::DigestBuildHasher::new(&digest::SHA256));
+/// map.insert("key", 1);
+/// assert_eq!(map.get("key"), Some(&1));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DigestBuildHasher {
+    algorithm: &'static Algorithm,
+}
+
+impl DigestBuildHasher {
+    /// Constructs a `DigestBuildHasher` whose hashers use `algorithm`.
+    pub fn new(algorithm: &'static Algorithm) -> Self {
+        Self { algorithm }
+    }
+
+    /// The algorithm that the hashers use.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+}
+
+impl BuildHasher for DigestBuildHasher {
+    type Hasher = DigestHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        DigestHasher {
+            ctx: Context::new(self.algorithm),
+        }
+    }
+}
+
+/// A [`Hasher`] backed by a digest [`Context`].
+///
+/// `finish` returns the first eight bytes of the digest of everything
+/// written so far, interpreted as a big-endian integer. See
+/// [`DigestBuildHasher`] for the caveats.
+#[derive(Clone)]
+pub struct DigestHasher {
+    ctx: Context,
+}
+
+impl Hasher for DigestHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.ctx.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        let digest = self.ctx.clone().finish();
+        let mut value = [0u8; 8];
+        value.copy_from_slice(&digest.as_ref()[..8]);
+        u64::from_be_bytes(value)
+    }
+}
+
+impl core::fmt::Debug for DigestHasher {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("DigestHasher")
+            .field("algorithm", self.ctx.algorithm())
+            .finish()
+    }
+}
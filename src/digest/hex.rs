@@ -0,0 +1,213 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Lowercase hex encoding, and hex decoding, of digest values.
+
+use crate::{cpu, polyfill::slice};
+
+#[cfg(feature = "std")]
+extern crate std;
+
+static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+
+/// Writes the lowercase hex encoding of `bytes` into `out`.
+///
+/// `out` must be exactly twice as long as `bytes`.
+pub(super) fn encode(bytes: &[u8], out: &mut [u8]) {
+    assert_eq!(out.len(), 2 * bytes.len());
+
+    let cpu_features = cpu::features();
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::SSSE3.available(cpu_features) {
+            // SAFETY: We just verified that SSSE3 is available.
+            unsafe { encode_ssse3(bytes, out) };
+            return;
+        }
+    }
+    let _ = cpu_features;
+
+    encode_swar(bytes, out)
+}
+
+// Encodes four bytes at a time using 64-bit arithmetic, with each input byte
+// spread over a 16-bit lane.
+fn encode_swar(bytes: &[u8], out: &mut [u8]) {
+    const LANES: u64 = 0x0101_0101_0101_0101;
+
+    let (chunks, rest) = slice::as_chunks::<u8, 4>(bytes);
+    let (out_chunks, out_rest) = slice::as_chunks_mut::<u8, 8>(out);
+    for (chunk, out) in chunks.iter().zip(out_chunks) {
+        let mut x = u64::from(u32::from_be_bytes(*chunk));
+        x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+        x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+        // Move the high nibble of each lane into the lane's upper byte.
+        x = ((x << 4) | x) & (0x0f * LANES);
+        // Each byte is now a nibble `n`; map it to `b'0' + n`, or to
+        // `b'a' + n - 10` when `n > 9`.
+        let letters = ((x + 0x06 * LANES) >> 4) & LANES;
+        x += u64::from(b'0') * LANES + letters * u64::from(b'a' - b'0' - 10);
+        *out = x.to_be_bytes();
+    }
+    encode_scalar(rest, out_rest);
+}
+
+fn encode_scalar(bytes: &[u8], out: &mut [u8]) {
+    for (&b, out) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+        out[0] = HEX_DIGITS[usize::from(b >> 4)];
+        out[1] = HEX_DIGITS[usize::from(b & 0x0f)];
+    }
+}
+
+// The intrinsics are safe to call from a `target_feature` function only as of
+// Rust 1.87, so the `unsafe` blocks are needed for older versions.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+#[allow(unused_unsafe)]
+unsafe fn encode_ssse3(bytes: &[u8], out: &mut [u8]) {
+    use core::arch::x86_64::{
+        __m128i, _mm_and_si128, _mm_loadu_si128, _mm_set1_epi8, _mm_shuffle_epi8, _mm_srli_epi16,
+        _mm_storeu_si128, _mm_unpackhi_epi8, _mm_unpacklo_epi8,
+    };
+
+    let (chunks, rest) = slice::as_chunks::<u8, 16>(bytes);
+    let (out_chunks, out_rest) = slice::as_chunks_mut::<u8, 32>(out);
+    // SAFETY: `HEX_DIGITS` is 16 bytes long.
+    let digits = unsafe { _mm_loadu_si128(HEX_DIGITS.as_ptr().cast::<__m128i>()) };
+    let low_nibbles = unsafe { _mm_set1_epi8(0x0f) };
+    for (chunk, out) in chunks.iter().zip(out_chunks) {
+        // SAFETY: `chunk` is 16 bytes long and `out` is 32 bytes long.
+        unsafe {
+            let input = _mm_loadu_si128(chunk.as_ptr().cast::<__m128i>());
+            let hi = _mm_and_si128(_mm_srli_epi16(input, 4), low_nibbles);
+            let lo = _mm_and_si128(input, low_nibbles);
+            let hi = _mm_shuffle_epi8(digits, hi);
+            let lo = _mm_shuffle_epi8(digits, lo);
+            let out = out.as_mut_ptr().cast::<__m128i>();
+            _mm_storeu_si128(out, _mm_unpacklo_epi8(hi, lo));
+            _mm_storeu_si128(out.add(1), _mm_unpackhi_epi8(hi, lo));
+        }
+    }
+    encode_swar(rest, out_rest);
+}
+
+/// Decodes the hex encoding `hex` into `out`, accepting either case.
+///
+/// `hex` must be exactly twice as long as `out`.
+pub(super) fn decode(hex: &[u8], out: &mut [u8]) -> Result<(), ParseDigestError> {
+    assert_eq!(hex.len(), 2 * out.len());
+
+    fn digit(d: u8) -> Result<u8, ParseDigestError> {
+        match d {
+            b'0'..=b'9' => Ok(d - b'0'),
+            b'a'..=b'f' => Ok(d - b'a' + 10),
+            b'A'..=b'F' => Ok(d - b'A' + 10),
+            _ => Err(ParseDigestError::BadChar),
+        }
+    }
+
+    for (pair, out) in hex.chunks_exact(2).zip(out) {
+        *out = (digit(pair[0])? << 4) | digit(pair[1])?;
+    }
+    Ok(())
+}
+
+/// An error parsing a digest from hex with
+/// [`Digest::from_hex`](super::Digest::from_hex).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseDigestError {
+    /// The input has an odd number of characters.
+    OddLength,
+
+    /// The input contains a character that isn't a hex digit.
+    BadChar,
+
+    /// The input doesn't have the length of the hex encoding of the
+    /// algorithm's output.
+    WrongLength {
+        /// The required number of hex digits.
+        expected: usize,
+        /// The actual number of hex digits.
+        actual: usize,
+    },
+}
+
+impl core::fmt::Display for ParseDigestError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::OddLength => f.write_str("OddLength"),
+            Self::BadChar => f.write_str("BadChar"),
+            Self::WrongLength { expected, actual } => write!(
+                f,
+                "WrongLength {{ expected: {}, actual: {} }}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDigestError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(encode: impl Fn(&[u8], &mut [u8])) {
+        let data: [u8; 100] = core::array::from_fn(|i| (i * 73 + 11).to_le_bytes()[0]);
+        let all: [u8; 256] = core::array::from_fn(|i| i.to_le_bytes()[0]);
+        for input in [&data[..], &all[..]] {
+            for start in 0..17 {
+                for end in start..input.len() {
+                    let input = &input[start..end];
+                    let mut actual = [0u8; 512];
+                    let actual = &mut actual[..2 * input.len()];
+                    encode(input, actual);
+                    for (b, actual) in input.iter().zip(actual.chunks_exact(2)) {
+                        let expected = [
+                            char::from_digit(u32::from(b >> 4), 16).unwrap(),
+                            char::from_digit(u32::from(b & 0x0f), 16).unwrap(),
+                        ];
+                        assert_eq!([char::from(actual[0]), char::from(actual[1])], expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scalar_matches_reference() {
+        check(encode_scalar);
+    }
+
+    #[test]
+    fn swar_matches_reference() {
+        check(encode_swar);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn ssse3_matches_reference() {
+        if !cpu::intel::SSSE3.available(cpu::features()) {
+            return;
+        }
+        check(|bytes, out| unsafe { encode_ssse3(bytes, out) });
+    }
+
+    #[test]
+    fn encode_matches_reference() {
+        check(encode);
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Algorithm, Context, Digest};
+use crate::polyfill;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// A [`Context`] that refuses to absorb more than a caller-chosen number of
+/// bytes.
+///
+/// This is intended for use as a denial-of-service mitigation when hashing
+/// attacker-controlled input. The limit is a policy chosen by the caller and
+/// is unrelated to the maximum input length of the digest algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use ring::digest;
+///
+/// let mut ctx = digest::LimitedContext::new(&digest::SHA256, 5);
+/// assert!(ctx.update(b"hello").is_ok());
+/// assert!(ctx.update(b"!").is_err());
+/// assert_eq!(ctx.finish().as_ref(), digest::digest(&digest::SHA256, b"hello").as_ref());
+/// ```
+#[derive(Clone)]
+pub struct LimitedContext {
+    inner: Context,
+    max_bytes: u64,
+    // Invariant: `self.len <= self.max_bytes`.
+    len: u64,
+}
+
+impl LimitedContext {
+    /// Constructs a new context that accepts at most `max_bytes` bytes of
+    /// input.
+    pub fn new(algorithm: &'static Algorithm, max_bytes: u64) -> Self {
+        Self {
+            inner: Context::new(algorithm),
+            max_bytes,
+            len: 0,
+        }
+    }
+
+    /// Updates the digest with all the data in `data`.
+    ///
+    /// If absorbing `data` would make the total input exceed the limit then
+    /// none of `data` is absorbed and `LimitExceeded` is returned.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), LimitExceeded> {
+        let len = self
+            .len
+            .checked_add(polyfill::u64_from_usize(data.len()))
+            .filter(|&len| len <= self.max_bytes)
+            .ok_or(LimitExceeded)?;
+        self.inner.update(data);
+        self.len = len;
+        Ok(())
+    }
+
+    /// Finalizes the digest calculation and returns the digest value.
+    pub fn finish(self) -> Digest {
+        self.inner.finish()
+    }
+
+    /// The algorithm that this context is using.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.inner.algorithm()
+    }
+}
+
+/// The input limit of a [`LimitedContext`] would have been exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitExceeded;
+
+impl core::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("LimitExceeded")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LimitExceeded {}
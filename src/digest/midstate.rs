@@ -0,0 +1,87 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Exporting and importing the chaining value at a block boundary.
+
+use super::{Algorithm, BlockContext, Context, Output, MAX_BLOCK_LEN};
+use crate::{error, polyfill};
+
+/// The chaining value of a [`Context`] at a block boundary, serialized
+/// big-endian as in the algorithm's output.
+///
+/// Use [`Self::as_ref`] to get the value, which is
+/// [`Algorithm::chaining_len()`] bytes long.
+#[derive(Clone)]
+pub struct Midstate {
+    value: Output,
+    len: usize,
+}
+
+impl AsRef<[u8]> for Midstate {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        &self.value.0[..self.len]
+    }
+}
+
+impl Context {
+    /// Returns the chaining value after the input absorbed so far, if the
+    /// input ends on a block boundary.
+    ///
+    /// Returns `None` if any input is buffered, since the chaining value
+    /// doesn't reflect a partial block. Unlike [`ContextData`], this is
+    /// only the compact, canonical chaining value; the number of bytes
+    /// absorbed must be tracked separately to restore the context with
+    /// [`Self::from_midstate`].
+    ///
+    /// [`ContextData`]: super::ContextData
+    pub fn midstate(&self) -> Option<Midstate> {
+        if self.num_pending != 0 {
+            return None;
+        }
+        let algorithm = self.block.algorithm;
+        Some(Midstate {
+            value: (algorithm.format_output)(self.block.state.clone()),
+            len: algorithm.chaining_len,
+        })
+    }
+
+    /// Constructs a context for `algorithm` that continues from the
+    /// chaining value `midstate` after `completed_bytes` bytes of input.
+    ///
+    /// Fails if `midstate` isn't `algorithm.chaining_len()` bytes long or
+    /// if `completed_bytes` isn't a multiple of `algorithm.block_len()`.
+    pub fn from_midstate(
+        algorithm: &'static Algorithm,
+        midstate: &[u8],
+        completed_bytes: u64,
+    ) -> Result<Self, error::Unspecified> {
+        if midstate.len() != algorithm.chaining_len
+            || completed_bytes % polyfill::u64_from_usize(algorithm.block_len()) != 0
+        {
+            return Err(error::Unspecified);
+        }
+
+        let mut block = BlockContext::new(algorithm);
+        block.state = algorithm.initial_state.with_be_bytes(midstate);
+        block.completed_bytes = u128::from(completed_bytes);
+        Ok(Self {
+            block,
+            pending: [0u8; MAX_BLOCK_LEN],
+            num_pending: 0,
+            sealed: false,
+            pending_cr: false,
+        })
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Algorithm, Context, Digest};
+use crate::error;
+use alloc::vec::Vec;
+
+#[cfg(feature = "serialize")]
+use super::{ContextData, DeserializeError, DigestResult};
+#[cfg(feature = "serialize")]
+use crate::polyfill;
+
+/// A context for calculating digests of the same input with several
+/// algorithms in one pass.
+///
+/// # Examples
+///
+/// ```
+/// use ring::digest;
+///
+/// let mut ctx =
+///     digest::MultiContext::new(&[&digest::SHA256, &digest::SHA512]).unwrap();
+/// ctx.update(b"hello, ");
+/// ctx.update(b"world");
+/// let digests = ctx.finish();
+/// assert_eq!(digests[0].as_ref(), digest::digest(&digest::SHA256, b"hello, world").as_ref());
+/// assert_eq!(digests[1].as_ref(), digest::digest(&digest::SHA512, b"hello, world").as_ref());
+/// ```
+#[derive(Clone)]
+pub struct MultiContext {
+    // Invariant: The algorithms of the contexts are distinct, and all the
+    // contexts have absorbed the same input.
+    contexts: Vec<Context>,
+}
+
+impl MultiContext {
+    /// Constructs a new context that uses each of `algorithms`.
+    ///
+    /// Fails if `algorithms` contains any algorithm more than once.
+    pub fn new(algorithms: &[&'static Algorithm]) -> Result<Self, error::Unspecified> {
+        if has_duplicates(algorithms.iter().copied()) {
+            return Err(error::Unspecified);
+        }
+        Ok(Self {
+            contexts: algorithms.iter().map(|alg| Context::new(alg)).collect(),
+        })
+    }
+
+    /// Updates the digests with all the data in `data`.
+    pub fn update(&mut self, data: &[u8]) {
+        self.contexts.iter_mut().for_each(|ctx| ctx.update(data));
+    }
+
+    /// Finalizes the digest calculations and returns the digest values, in
+    /// the same order as the algorithms were given to [`Self::new`].
+    pub fn finish(self) -> Vec<Digest> {
+        self.contexts.into_iter().map(Context::finish).collect()
+    }
+
+    /// The algorithms that this context is using, in order.
+    pub fn algorithms(&self) -> impl Iterator<Item = &'static Algorithm> + '_ {
+        self.contexts.iter().map(Context::algorithm)
+    }
+
+    /// Serializes the state of each of the contexts, in order.
+    ///
+    /// Fails if any of the algorithms is a custom one, which can't be
+    /// serialized.
+    #[cfg(feature = "serialize")]
+    pub fn serialize(&self) -> DigestResult<Vec<ContextData>> {
+        Ok(self
+            .contexts
+            .iter()
+            .map(ContextData::try_from)
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Restores a context that was serialized with [`Self::serialize`].
+    ///
+    /// Fails with the error from [`Context::deserialize`] if any of the
+    /// contexts is invalid, and with `DeserializeError::Malformed` if `data`
+    /// is empty, if an algorithm appears more than once, or if the contexts
+    /// haven't all absorbed the same number of bytes.
+    #[cfg(feature = "serialize")]
+    pub fn deserialize(data: Vec<ContextData>) -> DigestResult<Self> {
+        let contexts = data
+            .into_iter()
+            .map(Context::deserialize)
+            .collect::<DigestResult<Vec<_>>>()?;
+
+        let len = |ctx: &Context| {
+            ctx.block
+                .completed_bytes
+                .checked_add(u128::from(polyfill::u64_from_usize(ctx.num_pending)))
+        };
+        let first_len = contexts
+            .first()
+            .map(len)
+            .ok_or(DeserializeError::Malformed)?;
+        if contexts.iter().any(|ctx| len(ctx) != first_len)
+            || has_duplicates(contexts.iter().map(Context::algorithm))
+        {
+            return Err(DeserializeError::Malformed.into());
+        }
+        Ok(Self { contexts })
+    }
+}
+
+fn has_duplicates(algorithms: impl Iterator<Item = &'static Algorithm> + Clone) -> bool {
+    algorithms
+        .clone()
+        .enumerate()
+        .any(|(i, a)| algorithms.clone().skip(i + 1).any(|b| a == b))
+}
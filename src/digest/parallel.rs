@@ -0,0 +1,88 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Batch hashing of independent inputs across threads.
+
+use super::{digest, Algorithm, Digest};
+use alloc::vec::Vec;
+
+extern crate std;
+
+/// Below this many inputs per thread, spawning another thread costs more
+/// than it saves.
+const MIN_INPUTS_PER_THREAD: usize = 16;
+
+/// Computes the digest of each of `inputs`, choosing how many threads to use
+/// from the number of inputs.
+///
+/// SHA-1 and SHA-2 digests of a single message cannot be computed in
+/// parallel, so this only parallelizes *across* inputs: each input is hashed
+/// by exactly one thread and `result[i]` is always
+/// `digest(algorithm, inputs[i])`. Small batches are hashed on the calling
+/// thread; larger batches are split into contiguous chunks hashed on up to
+/// [`std::thread::available_parallelism`] scoped threads.
+///
+/// To hash one large buffer, use [`digest`]; there is no speedup to be had
+/// from this function for a single input.
+pub fn digest_auto(algorithm: &'static Algorithm, inputs: &[&[u8]]) -> Vec<Digest> {
+    let threads = thread_count(inputs.len());
+    if threads <= 1 {
+        return inputs
+            .iter()
+            .map(|input| digest(algorithm, input))
+            .collect();
+    }
+
+    let chunk_len = (inputs.len() + threads - 1) / threads;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .chunks(chunk_len)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|input| digest(algorithm, input))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn thread_count(num_inputs: usize) -> usize {
+    let wanted = num_inputs / MIN_INPUTS_PER_THREAD;
+    if wanted <= 1 {
+        return 1;
+    }
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    core::cmp::min(wanted, available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_count_scales_with_inputs() {
+        assert_eq!(thread_count(0), 1);
+        assert_eq!(thread_count(MIN_INPUTS_PER_THREAD * 2 - 1), 1);
+        let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+        assert_eq!(thread_count(MIN_INPUTS_PER_THREAD * 2), available.min(2));
+        assert_eq!(thread_count(usize::MAX), available);
+    }
+}
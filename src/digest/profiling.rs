@@ -0,0 +1,117 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Algorithm, Context, Digest};
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// A [`Context`] that also counts how often each byte value occurs in its
+/// input.
+#[derive(Clone)]
+pub struct ProfilingContext {
+    inner: Context,
+    histogram: ByteHistogram,
+}
+
+impl ProfilingContext {
+    /// Constructs a new context.
+    pub fn new(algorithm: &'static Algorithm) -> Self {
+        Self {
+            inner: Context::new(algorithm),
+            histogram: ByteHistogram::new(),
+        }
+    }
+
+    /// Updates the digest and the byte histogram with all the data in
+    /// `data`.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+        self.histogram.record(data);
+    }
+
+    /// The byte histogram of the input absorbed so far.
+    pub fn histogram(&self) -> ByteHistogram {
+        self.histogram.clone()
+    }
+
+    /// Finalizes the digest calculation and returns the digest value.
+    pub fn finish(self) -> Digest {
+        self.inner.finish()
+    }
+
+    /// The algorithm that this context is using.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.inner.algorithm()
+    }
+}
+
+/// The number of occurrences of each byte value in some input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteHistogram {
+    counts: [u64; 256],
+}
+
+impl ByteHistogram {
+    /// Constructs an empty histogram.
+    pub fn new() -> Self {
+        Self { counts: [0; 256] }
+    }
+
+    fn record(&mut self, data: &[u8]) {
+        data.iter().for_each(|&b| self.counts[usize::from(b)] += 1);
+    }
+
+    /// Adds the counts of `other` to `self`, as if the input counted by
+    /// `other` had been counted by `self` too.
+    ///
+    /// This allows the histograms of the shards of an input, hashed in
+    /// parallel, to be combined.
+    pub fn merge(&mut self, other: &ByteHistogram) {
+        self.counts
+            .iter_mut()
+            .zip(other.counts.iter())
+            .for_each(|(count, other)| *count += other);
+    }
+
+    /// The number of occurrences of each byte value, indexed by the value.
+    pub fn counts(&self) -> &[u64; 256] {
+        &self.counts
+    }
+
+    /// The total number of bytes counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The Shannon entropy of the byte distribution, in bits per byte.
+    ///
+    /// This is between 0, for empty input or input consisting of a single
+    /// repeated byte, and 8, for input where every byte value is equally
+    /// frequent.
+    #[cfg(feature = "std")]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn entropy_bits(&self) -> f64 {
+        let total = self.total() as f64;
+        self.counts
+            .iter()
+            .filter(|&&count| count != 0)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum()
+    }
+}
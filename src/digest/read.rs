@@ -0,0 +1,47 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Context, MAX_BLOCK_LEN};
+use crate::polyfill;
+use std::io;
+
+extern crate std;
+
+/// The size of the buffer that `update_reader` reads into, a whole number of
+/// blocks for every algorithm.
+const READ_BUFFER_LEN: usize = 64 * MAX_BLOCK_LEN;
+
+impl Context {
+    /// Reads `reader` to the end, updating the digest with everything read,
+    /// and returns the number of bytes read.
+    ///
+    /// Reads that fail with `std::io::ErrorKind::Interrupted` are retried.
+    /// Any other error is returned immediately; the context has then been
+    /// updated with exactly the bytes read before the error, so the caller
+    /// may resume reading from where the reader left off.
+    pub fn update_reader<R: io::Read>(&mut self, reader: &mut R) -> io::Result<u64> {
+        let mut buffer = [0u8; READ_BUFFER_LEN];
+        let mut total = 0u64;
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => return Ok(total),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.update(&buffer[..n]);
+            total += polyfill::u64_from_usize(n);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{CannotUnbuffer, LimitExceeded, ParseDigestError};
+use crate::error;
+
+#[cfg(feature = "serialize")]
+use super::DeserializeError;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+/// The result of a fallible operation in this module, with any of its errors.
+///
+/// Functions that parse, restore or write out digests and contexts return
+/// a `DigestResult`. Other functions that can fail in only one way return a
+/// more specific error type, so that callers can match on it precisely.
+/// Every such error converts into [`DigestError`], so `?` can be used to
+/// propagate errors from different functions through a single
+/// `DigestResult`:
+///
+/// ```
+/// use ring::digest::{self, DigestResult};
+///
+/// fn prefix(data: &[u8]) -> DigestResult<Vec<u8>> {
+///     let mut ctx = digest::LimitedContext::new(&digest::SHA256, 1024);
+///     ctx.update(data)?; // `LimitExceeded`
+///     let mut ctx = digest::Context::new(&digest::SHA256);
+///     ctx.update(data);
+///     let truncated = ctx.finish_truncated(8)?; // `error::Unspecified`
+///     Ok(truncated.as_ref().to_vec())
+/// }
+///
+/// assert_eq!(prefix(b"hello").unwrap().len(), 8);
+/// assert_eq!(prefix(&[0; 2048]), Err(digest::DigestError::LimitExceeded));
+/// ```
+pub type DigestResult<T> = Result<T, DigestError>;
+
+/// Any error returned by this module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DigestError {
+    /// See [`error::Unspecified`].
+    Unspecified,
+
+    /// See [`LimitExceeded`].
+    LimitExceeded,
+
+    /// See [`CannotUnbuffer`].
+    CannotUnbuffer,
+
+    /// See [`BufferTooSmall`].
+    BufferTooSmall,
+
+    /// See [`LengthOverflow`].
+    LengthOverflow,
+
+    /// See [`TruncationError`].
+    Truncation,
+
+    /// See [`CustomAlgorithm`].
+    CustomAlgorithm,
+
+    /// See [`VerificationError`].
+    Verification(VerificationError),
+
+    /// See [`ParseDigestError`].
+    ParseDigest(ParseDigestError),
+
+    /// See [`DeserializeError`].
+    #[cfg(feature = "serialize")]
+    Deserialize(DeserializeError),
+}
+
+impl From<error::Unspecified> for DigestError {
+    fn from(_: error::Unspecified) -> Self {
+        Self::Unspecified
+    }
+}
+
+impl From<LimitExceeded> for DigestError {
+    fn from(_: LimitExceeded) -> Self {
+        Self::LimitExceeded
+    }
+}
+
+impl From<CannotUnbuffer> for DigestError {
+    fn from(_: CannotUnbuffer) -> Self {
+        Self::CannotUnbuffer
+    }
+}
+
+impl From<BufferTooSmall> for DigestError {
+    fn from(_: BufferTooSmall) -> Self {
+        Self::BufferTooSmall
+    }
+}
+
+impl From<LengthOverflow> for DigestError {
+    fn from(_: LengthOverflow) -> Self {
+        Self::LengthOverflow
+    }
+}
+
+impl From<TruncationError> for DigestError {
+    fn from(_: TruncationError) -> Self {
+        Self::Truncation
+    }
+}
+
+impl From<CustomAlgorithm> for DigestError {
+    fn from(_: CustomAlgorithm) -> Self {
+        Self::CustomAlgorithm
+    }
+}
+
+impl From<VerificationError> for DigestError {
+    fn from(error: VerificationError) -> Self {
+        Self::Verification(error)
+    }
+}
+
+impl From<ParseDigestError> for DigestError {
+    fn from(error: ParseDigestError) -> Self {
+        Self::ParseDigest(error)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl From<DeserializeError> for DigestError {
+    fn from(error: DeserializeError) -> Self {
+        Self::Deserialize(error)
+    }
+}
+
+impl core::fmt::Display for DigestError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Unspecified => f.write_str("Unspecified"),
+            Self::LimitExceeded => f.write_str("LimitExceeded"),
+            Self::CannotUnbuffer => f.write_str("CannotUnbuffer"),
+            Self::BufferTooSmall => f.write_str("BufferTooSmall"),
+            Self::LengthOverflow => f.write_str("LengthOverflow"),
+            Self::Truncation => f.write_str("TruncationError"),
+            Self::CustomAlgorithm => f.write_str("CustomAlgorithm"),
+            Self::Verification(error) => core::fmt::Display::fmt(error, f),
+            Self::ParseDigest(error) => core::fmt::Display::fmt(error, f),
+            #[cfg(feature = "serialize")]
+            Self::Deserialize(error) => core::fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DigestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        if let Self::ParseDigest(error) = self {
+            return Some(error);
+        }
+        if let Self::Verification(error) = self {
+            return Some(error);
+        }
+        #[cfg(feature = "serialize")]
+        {
+            if let Self::Deserialize(error) = self {
+                return Some(error);
+            }
+        }
+        None
+    }
+}
+
+/// A caller-provided output buffer is too small.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BufferTooSmall;
+
+impl core::fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("BufferTooSmall")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmall {}
+
+/// More than an algorithm's maximum input length was input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthOverflow;
+
+impl core::fmt::Display for LengthOverflow {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("LengthOverflow")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthOverflow {}
+
+/// A digest was asked for more bytes than its output length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TruncationError;
+
+impl core::fmt::Display for TruncationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("TruncationError")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TruncationError {}
+
+/// An algorithm built with [`super::AlgorithmBuilder`] was used where an
+/// [`super::AlgorithmId`] is needed, e.g. to serialize a context; custom
+/// algorithms have no id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CustomAlgorithm;
+
+impl core::fmt::Display for CustomAlgorithm {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("CustomAlgorithm")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CustomAlgorithm {}
+
+/// A digest didn't match the expected value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The expected value isn't as long as the algorithm's output.
+    WrongLength {
+        /// The algorithm's output length.
+        expected: usize,
+        /// The length of the expected value.
+        actual: usize,
+    },
+
+    /// The digest value differs from the expected value.
+    Mismatch,
+}
+
+impl core::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::WrongLength { expected, actual } => write!(
+                f,
+                "WrongLength {{ expected: {}, actual: {} }}",
+                expected, actual
+            ),
+            Self::Mismatch => f.write_str("Mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerificationError {}
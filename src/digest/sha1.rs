@@ -17,7 +17,7 @@ use super::{
     sha2::{ch, maj, State32, Word},
     BlockLen, OutputLen,
 };
-use crate::polyfill::slice;
+use crate::{cpu, polyfill::slice};
 use core::num::Wrapping;
 
 pub(super) const BLOCK_LEN: BlockLen = BlockLen::_512;
@@ -36,12 +36,23 @@ fn parity(x: W32, y: W32, z: W32) -> W32 {
 type State = [W32; CHAINING_WORDS];
 const ROUNDS: usize = 80;
 
-pub fn sha1_block_data_order(state: &mut State32, data: &[[u8; BLOCK_LEN.into()]]) {
+pub fn sha1_block_data_order(
+    state: &mut State32,
+    data: &[[u8; BLOCK_LEN.into()]],
+    cpu_features: cpu::Features,
+) {
     // The unwrap won't fail because `CHAINING_WORDS` is smaller than the
     // length.
     let state: &mut State = (&mut state[..CHAINING_WORDS]).try_into().unwrap();
-    // SAFETY: The caller guarantees that this is called with data pointing to `num`
-    // `BLOCK_LEN`-long blocks.
+
+    #[cfg(target_arch = "aarch64")]
+    if cpu::arm::SHA1.available(cpu_features) {
+        // SAFETY: The CPU supports the SHA-1 instructions.
+        unsafe { aarch64::block_data_order(state, data) };
+        return;
+    }
+
+    let _cpu_features = cpu_features;
     *state = block_data_order(*state, data)
 }
 
@@ -114,3 +125,164 @@ fn step3(
 fn rotl(x: W32, n: u32) -> W32 {
     Wrapping(x.0.rotate_left(n))
 }
+
+// The SHA-1 intrinsics were stabilized after our MSRV, so the SHA-1
+// instructions are issued with `asm!`; everything else uses the NEON
+// intrinsics.
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use super::{State, BLOCK_LEN};
+    use core::arch::{aarch64::*, asm};
+
+    // FIPS 180-4 4.2.1, one constant per 20 rounds.
+    const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+    // SAFETY: The caller must ensure that the CPU supports the SHA-1
+    // instructions.
+    #[target_feature(enable = "neon,sha2")]
+    #[allow(unused_unsafe)]
+    pub(super) unsafe fn block_data_order(state: &mut State, data: &[[u8; BLOCK_LEN.into()]]) {
+        unsafe {
+            let abcd = [state[0].0, state[1].0, state[2].0, state[3].0];
+            let mut abcd = vld1q_u32(abcd.as_ptr());
+            let mut e = state[4].0;
+
+            for block in data {
+                let (abcd_before, e_before) = (abcd, e);
+
+                // `w[g % 4]` holds the message schedule words `4g..4g + 4`
+                // for the group of four rounds `g`.
+                let mut w = [vdupq_n_u32(0); 4];
+                for (w, bytes) in w.iter_mut().zip(block.chunks_exact(16)) {
+                    *w = vreinterpretq_u32_u8(vrev32q_u8(vld1q_u8(bytes.as_ptr())));
+                }
+
+                for g in 0..20 {
+                    let wk = vaddq_u32(w[g % 4], vdupq_n_u32(K[g / 5]));
+                    // `e` for the next group is `a` of this one rotated.
+                    let next_e = sha1h(vgetq_lane_u32(abcd, 0));
+                    abcd = match g / 5 {
+                        0 => sha1c(abcd, e, wk),
+                        2 => sha1m(abcd, e, wk),
+                        _ => sha1p(abcd, e, wk),
+                    };
+                    e = next_e;
+                    if g < 16 {
+                        let t = sha1su0(w[g % 4], w[(g + 1) % 4], w[(g + 2) % 4]);
+                        w[g % 4] = sha1su1(t, w[(g + 3) % 4]);
+                    }
+                }
+
+                abcd = vaddq_u32(abcd, abcd_before);
+                e = e.wrapping_add(e_before);
+            }
+
+            let mut out = [0u32; 4];
+            vst1q_u32(out.as_mut_ptr(), abcd);
+            for (h, out) in state.iter_mut().zip(out.iter().chain(core::iter::once(&e))) {
+                h.0 = *out;
+            }
+        }
+    }
+
+    macro_rules! sha1_hash_update {
+        ( $name:ident, $instruction:literal ) => {
+            #[target_feature(enable = "sha2")]
+            #[inline]
+            #[allow(unused_unsafe)]
+            unsafe fn $name(mut abcd: uint32x4_t, e: u32, wk: uint32x4_t) -> uint32x4_t {
+                unsafe {
+                    asm!(
+                        concat!($instruction, " {abcd:q}, {e:s}, {wk:v}.4s"),
+                        abcd = inout(vreg) abcd,
+                        e = in(vreg) e,
+                        wk = in(vreg) wk,
+                        options(pure, nomem, nostack, preserves_flags),
+                    );
+                }
+                abcd
+            }
+        };
+    }
+
+    sha1_hash_update!(sha1c, "sha1c");
+    sha1_hash_update!(sha1m, "sha1m");
+    sha1_hash_update!(sha1p, "sha1p");
+
+    #[target_feature(enable = "sha2")]
+    #[inline]
+    #[allow(unused_unsafe)]
+    unsafe fn sha1h(a: u32) -> u32 {
+        let e: u32;
+        unsafe {
+            asm!(
+                "sha1h {e:s}, {a:s}",
+                e = out(vreg) e,
+                a = in(vreg) a,
+                options(pure, nomem, nostack, preserves_flags),
+            );
+        }
+        e
+    }
+
+    #[target_feature(enable = "sha2")]
+    #[inline]
+    #[allow(unused_unsafe)]
+    unsafe fn sha1su0(mut w0: uint32x4_t, w1: uint32x4_t, w2: uint32x4_t) -> uint32x4_t {
+        unsafe {
+            asm!(
+                "sha1su0 {w0:v}.4s, {w1:v}.4s, {w2:v}.4s",
+                w0 = inout(vreg) w0,
+                w1 = in(vreg) w1,
+                w2 = in(vreg) w2,
+                options(pure, nomem, nostack, preserves_flags),
+            );
+        }
+        w0
+    }
+
+    #[target_feature(enable = "sha2")]
+    #[inline]
+    #[allow(unused_unsafe)]
+    unsafe fn sha1su1(mut t: uint32x4_t, w3: uint32x4_t) -> uint32x4_t {
+        unsafe {
+            asm!(
+                "sha1su1 {t:v}.4s, {w3:v}.4s",
+                t = inout(vreg) t,
+                w3 = in(vreg) w3,
+                options(pure, nomem, nostack, preserves_flags),
+            );
+        }
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::{SecureRandom, SystemRandom};
+
+    // On CPUs with the SHA-1 instructions, `sha1_block_data_order` uses them.
+    #[test]
+    fn block_data_order_matches_portable() {
+        let rng = SystemRandom::new();
+        for num_blocks in 0..=9 {
+            let mut state = State32::default();
+            let mut blocks = [[0u8; BLOCK_LEN.into()]; 9];
+            for w in state.iter_mut() {
+                let mut bytes = [0u8; 4];
+                rng.fill(&mut bytes).unwrap();
+                *w = Wrapping(u32::from_be_bytes(bytes));
+            }
+            for block in blocks.iter_mut() {
+                rng.fill(block).unwrap();
+            }
+            let blocks = &blocks[..num_blocks];
+
+            let h: State = state[..CHAINING_WORDS].try_into().unwrap();
+            let expected = block_data_order(h, blocks);
+            sha1_block_data_order(&mut state, blocks, cpu::features());
+            assert_eq!(state[..CHAINING_WORDS], expected, "{} blocks", blocks.len());
+        }
+    }
+}
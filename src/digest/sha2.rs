@@ -133,6 +133,58 @@ where
     H
 }
 
+/// Runs the SHA-256 compression function over a single block, recording the
+/// message schedule and the working variables after each round.
+///
+/// This mirrors `block_data_order` step for step but is never used to compute
+/// digests.
+#[cfg(feature = "educational")]
+pub(super) fn trace_block_32(
+    H: State32,
+    M: &[u8; SHA256_BLOCK_LEN.into()],
+) -> ([Wrapping<u32>; 64], [State32; 64], State32) {
+    type S = Wrapping<u32>;
+
+    let (M, remainder): (&[[u8; 4]], &[u8]) = slice::as_chunks(M);
+    debug_assert!(remainder.is_empty());
+
+    // FIPS 180-4 6.2.2 Step 1
+    let mut W = [S::ZERO; 64];
+    for (W, M) in W.iter_mut().zip(M) {
+        *W = S::from_be_bytes(*M);
+    }
+    for t in M.len()..W.len() {
+        W[t] = sigma_1(W[t - 2]) + W[t - 7] + sigma_0(W[t - 15]) + W[t - 16]
+    }
+
+    // FIPS 180-4 6.2.2 Step 2
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = H;
+
+    // FIPS 180-4 6.2.2 Step 3
+    let mut rounds = [[S::ZERO; CHAINING_WORDS]; 64];
+    for ((Kt, Wt), round) in S::K.iter().zip(W.iter()).zip(rounds.iter_mut()) {
+        let T1 = h + SIGMA_1(e) + ch(e, f, g) + *Kt + *Wt;
+        let T2 = SIGMA_0(a) + maj(a, b, c);
+        h = g;
+        g = f;
+        f = e;
+        e = d + T1;
+        d = c;
+        c = b;
+        b = a;
+        a = T1 + T2;
+        *round = [a, b, c, d, e, f, g, h];
+    }
+
+    // FIPS 180-4 6.2.2 Step 4
+    let mut out = H;
+    for (out, v) in out.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *out += v;
+    }
+
+    (W, rounds, out)
+}
+
 // FIPS 180-4 {4.1.1, 4.1.2, 4.1.3}
 #[inline(always)]
 pub(super) fn ch<W: Word>(x: W, y: W, z: W) -> W {
@@ -296,6 +348,9 @@ impl Sha2 for Wrapping<u32> {
     ];
 }
 
+#[cfg(target_arch = "x86_64")]
+pub(super) const SHA256_K: &[Wrapping<u32>] = <Wrapping<u32> as Sha2>::K;
+
 impl Word for Wrapping<u64> {
     const ZERO: Self = Self(0);
     type InputBytes = [u8; 8];
@@ -417,3 +472,58 @@ prefixed_extern! {
         num: crate::c::NonZero_size_t,
     );
 }
+
+// Checks the dispatched implementations, which use the CPU's SHA extensions
+// when they are available, against the portable one.
+#[cfg(all(
+    test,
+    any(target_arch = "aarch64", target_arch = "arm", target_arch = "x86_64")
+))]
+mod tests {
+    use super::*;
+    use crate::rand::{SecureRandom, SystemRandom};
+
+    #[test]
+    fn block_data_order_32_matches_portable() {
+        let rng = SystemRandom::new();
+        for num_blocks in 0..=9 {
+            let mut state = [Wrapping(0u32); CHAINING_WORDS];
+            let mut blocks = [[0u8; SHA256_BLOCK_LEN.into()]; 9];
+            for w in state.iter_mut() {
+                let mut bytes = [0u8; 4];
+                rng.fill(&mut bytes).unwrap();
+                *w = Wrapping(u32::from_be_bytes(bytes));
+            }
+            for block in blocks.iter_mut() {
+                rng.fill(block).unwrap();
+            }
+            let blocks = &blocks[..num_blocks];
+
+            let expected = block_data_order(state, blocks);
+            block_data_order_32(&mut state, blocks, cpu::features());
+            assert_eq!(state, expected, "{} blocks", blocks.len());
+        }
+    }
+
+    #[test]
+    fn block_data_order_64_matches_portable() {
+        let rng = SystemRandom::new();
+        for num_blocks in 0..=9 {
+            let mut state = [Wrapping(0u64); CHAINING_WORDS];
+            let mut blocks = [[0u8; SHA512_BLOCK_LEN.into()]; 9];
+            for w in state.iter_mut() {
+                let mut bytes = [0u8; 8];
+                rng.fill(&mut bytes).unwrap();
+                *w = Wrapping(u64::from_be_bytes(bytes));
+            }
+            for block in blocks.iter_mut() {
+                rng.fill(block).unwrap();
+            }
+            let blocks = &blocks[..num_blocks];
+
+            let expected = block_data_order(state, blocks);
+            block_data_order_64(&mut state, blocks, cpu::features());
+            assert_eq!(state, expected, "{} blocks", blocks.len());
+        }
+    }
+}
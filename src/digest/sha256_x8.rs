@@ -0,0 +1,217 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! SHA-256 of eight single-block messages at once.
+
+use super::{digest, SHA256, SHA256_OUTPUT_LEN};
+use crate::cpu;
+
+/// Returns the SHA-256 digests of eight 64-byte messages.
+///
+/// `result[i]` is always `digest(&SHA256, &blocks[i])`. On x86-64 CPUs with
+/// AVX2 the eight messages are hashed together, one per 32-bit lane, which is
+/// much faster than hashing them one after another; elsewhere they are
+/// hashed one after another.
+pub fn sha256_x8(blocks: &[[u8; 64]; 8]) -> [[u8; SHA256_OUTPUT_LEN]; 8] {
+    let cpu_features = cpu::features();
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if cpu::intel::AVX2.available(cpu_features) {
+            // SAFETY: We just verified that AVX2 is available.
+            return unsafe { avx2::sha256_x8(blocks) };
+        }
+    }
+    let _ = cpu_features;
+
+    sha256_x8_sequential(blocks)
+}
+
+fn sha256_x8_sequential(blocks: &[[u8; 64]; 8]) -> [[u8; SHA256_OUTPUT_LEN]; 8] {
+    let mut out = [[0u8; SHA256_OUTPUT_LEN]; 8];
+    for (out, block) in out.iter_mut().zip(blocks) {
+        out.copy_from_slice(digest(&SHA256, block).as_ref());
+    }
+    out
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use super::{
+        super::{dynstate::DynState, sha2},
+        SHA256, SHA256_OUTPUT_LEN,
+    };
+    use crate::polyfill::slice;
+    use core::arch::x86_64::{
+        __m256i, _mm256_add_epi32, _mm256_and_si256, _mm256_andnot_si256, _mm256_loadu_si256,
+        _mm256_or_si256, _mm256_set1_epi32, _mm256_setzero_si256, _mm256_slli_epi32,
+        _mm256_srli_epi32, _mm256_storeu_si256, _mm256_xor_si256,
+    };
+
+    // Each vector holds the same 32-bit word of eight independent states or
+    // messages, one per lane.
+    type Words = __m256i;
+
+    macro_rules! rotr {
+        ($x:expr, $n:literal) => {
+            _mm256_or_si256(
+                _mm256_srli_epi32::<$n>($x),
+                _mm256_slli_epi32::<{ 32 - $n }>($x),
+            )
+        };
+    }
+
+    // The intrinsics are safe to call from a `target_feature` function only
+    // as of Rust 1.87, so the `unsafe` blocks are needed for older versions.
+    #[target_feature(enable = "avx2")]
+    #[allow(unused_unsafe)]
+    pub(super) unsafe fn sha256_x8(blocks: &[[u8; 64]; 8]) -> [[u8; SHA256_OUTPUT_LEN]; 8] {
+        // Transpose the messages so that `words[t]` holds word `t` of each.
+        let mut words = [[0u32; 8]; 16];
+        for (lane, block) in blocks.iter().enumerate() {
+            let (block_words, _) = slice::as_chunks::<u8, 4>(block);
+            for (t, w) in block_words.iter().enumerate() {
+                words[t][lane] = u32::from_be_bytes(*w);
+            }
+        }
+        let mut message = [unsafe { _mm256_setzero_si256() }; 16];
+        for (m, w) in message.iter_mut().zip(&words) {
+            // SAFETY: `w` is 32 bytes long.
+            *m = unsafe { _mm256_loadu_si256(w.as_ptr().cast::<Words>()) };
+        }
+
+        let iv = match SHA256.initial_state {
+            DynState::As32(iv) => iv,
+            DynState::As64(_) => unreachable!(),
+        };
+        let mut state = [unsafe { _mm256_setzero_si256() }; 8];
+        for (s, iv) in state.iter_mut().zip(iv) {
+            *s = unsafe { splat(iv.0) };
+        }
+
+        unsafe { compress(&mut state, &message) };
+
+        // Every message is exactly one block long, so the padding block is
+        // the same for all of them: 0x80, zeros, and the 512-bit length.
+        let mut padding = [unsafe { _mm256_setzero_si256() }; 16];
+        padding[0] = unsafe { splat(0x8000_0000) };
+        padding[15] = unsafe { splat(512) };
+        unsafe { compress(&mut state, &padding) };
+
+        let mut out = [[0u8; SHA256_OUTPUT_LEN]; 8];
+        for (i, s) in state.iter().enumerate() {
+            let mut lanes = [0u32; 8];
+            // SAFETY: `lanes` is 32 bytes long.
+            unsafe { _mm256_storeu_si256(lanes.as_mut_ptr().cast::<Words>(), *s) };
+            for (out, lane) in out.iter_mut().zip(lanes) {
+                out[(4 * i)..(4 * (i + 1))].copy_from_slice(&lane.to_be_bytes());
+            }
+        }
+        out
+    }
+
+    #[target_feature(enable = "avx2")]
+    #[allow(unused_unsafe)]
+    unsafe fn splat(w: u32) -> Words {
+        unsafe { _mm256_set1_epi32(i32::from_ne_bytes(w.to_ne_bytes())) }
+    }
+
+    // FIPS 180-4 6.2.2, in each lane.
+    #[target_feature(enable = "avx2")]
+    #[allow(unused_unsafe, non_snake_case)]
+    unsafe fn compress(state: &mut [Words; 8], block: &[Words; 16]) {
+        unsafe {
+            let mut W = [_mm256_setzero_si256(); 64];
+            W[..16].copy_from_slice(block);
+            for t in 16..64 {
+                let s0 = _mm256_xor_si256(
+                    _mm256_xor_si256(rotr!(W[t - 15], 7), rotr!(W[t - 15], 18)),
+                    _mm256_srli_epi32::<3>(W[t - 15]),
+                );
+                let s1 = _mm256_xor_si256(
+                    _mm256_xor_si256(rotr!(W[t - 2], 17), rotr!(W[t - 2], 19)),
+                    _mm256_srli_epi32::<10>(W[t - 2]),
+                );
+                W[t] = _mm256_add_epi32(
+                    _mm256_add_epi32(W[t - 16], s0),
+                    _mm256_add_epi32(W[t - 7], s1),
+                );
+            }
+
+            let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+            for (Kt, Wt) in sha2::SHA256_K.iter().zip(W.iter()) {
+                let S1 =
+                    _mm256_xor_si256(_mm256_xor_si256(rotr!(e, 6), rotr!(e, 11)), rotr!(e, 25));
+                let ch = _mm256_xor_si256(_mm256_and_si256(e, f), _mm256_andnot_si256(e, g));
+                let T1 = _mm256_add_epi32(
+                    _mm256_add_epi32(_mm256_add_epi32(h, S1), _mm256_add_epi32(ch, *Wt)),
+                    splat(Kt.0),
+                );
+                let S0 =
+                    _mm256_xor_si256(_mm256_xor_si256(rotr!(a, 2), rotr!(a, 13)), rotr!(a, 22));
+                let maj = _mm256_or_si256(
+                    _mm256_and_si256(a, b),
+                    _mm256_and_si256(c, _mm256_or_si256(a, b)),
+                );
+                let T2 = _mm256_add_epi32(S0, maj);
+                h = g;
+                g = f;
+                f = e;
+                e = _mm256_add_epi32(d, T1);
+                d = c;
+                c = b;
+                b = a;
+                a = _mm256_add_epi32(T1, T2);
+            }
+
+            for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+                *s = _mm256_add_epi32(*s, v);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(seed: u8) -> [[u8; 64]; 8] {
+        core::array::from_fn(|lane| {
+            core::array::from_fn(|i| (i * 31 + lane * 7).to_le_bytes()[0] ^ seed)
+        })
+    }
+
+    #[test]
+    fn sequential_matches_digest() {
+        let blocks = blocks(0);
+        for (out, block) in sha256_x8_sequential(&blocks).iter().zip(&blocks) {
+            assert_eq!(&out[..], digest(&SHA256, block).as_ref());
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn avx2_matches_sequential() {
+        if !cpu::intel::AVX2.available(cpu::features()) {
+            return;
+        }
+        for seed in [0x00, 0x5a, 0xff] {
+            let blocks = blocks(seed);
+            assert_eq!(
+                unsafe { avx2::sha256_x8(&blocks) },
+                sha256_x8_sequential(&blocks)
+            );
+        }
+    }
+}
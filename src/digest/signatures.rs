@@ -0,0 +1,118 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Fixed-size block signatures of files, e.g. for rsync-style delta sync,
+//! and localizing the differences between files.
+
+use super::{digest, Algorithm, Digest};
+use alloc::vec::Vec;
+use std::{fs::File, io, io::Read, path::Path};
+
+extern crate std;
+
+/// Returns the digest of each consecutive `block_size`-byte block of the
+/// file at `path`.
+///
+/// The blocks don't overlap, and the last block is shorter than
+/// `block_size` if the file's length isn't a multiple of it. An empty file
+/// has no blocks. The file is streamed, one block at a time, rather than
+/// read into memory at once.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `block_size` is zero.
+pub fn file_block_signatures<P: AsRef<Path>>(
+    algorithm: &'static Algorithm,
+    path: P,
+    block_size: usize,
+) -> io::Result<Vec<Digest>> {
+    if block_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "block_size must not be zero",
+        ));
+    }
+
+    let mut file = File::open(path)?;
+    let mut buffer = alloc::vec![0u8; block_size];
+    let mut signatures = Vec::new();
+    loop {
+        let len = read_block(&mut file, &mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        signatures.push(digest(algorithm, &buffer[..len]));
+        if len < block_size {
+            break;
+        }
+    }
+    Ok(signatures)
+}
+
+/// Returns the index of the first `block_size`-byte block at which the files
+/// at `a` and `b` differ, or `None` if their contents are identical.
+///
+/// Blocks are compared by their digests, so this is only meant to cheaply
+/// localize differences. If one file is a prefix of the other, the result
+/// is the index of the block in which the shorter file ends. Both files are
+/// streamed, one block at a time, and reading stops at the first
+/// difference.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `block_size` is zero.
+pub fn first_differing_block<P: AsRef<Path>>(
+    algorithm: &'static Algorithm,
+    a: P,
+    b: P,
+    block_size: usize,
+) -> io::Result<Option<u64>> {
+    if block_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "block_size must not be zero",
+        ));
+    }
+
+    let mut a = File::open(a)?;
+    let mut b = File::open(b)?;
+    let mut buffer_a = alloc::vec![0u8; block_size];
+    let mut buffer_b = alloc::vec![0u8; block_size];
+    let mut index = 0u64;
+    loop {
+        let len_a = read_block(&mut a, &mut buffer_a)?;
+        let len_b = read_block(&mut b, &mut buffer_b)?;
+        if len_a != len_b
+            || digest(algorithm, &buffer_a[..len_a]).as_ref()
+                != digest(algorithm, &buffer_b[..len_b]).as_ref()
+        {
+            return Ok(Some(index));
+        }
+        if len_a < block_size {
+            return Ok(None);
+        }
+        index += 1;
+    }
+}
+
+/// Fills `buffer` from `reader`, stopping early only at the end of the
+/// input. Returns the number of bytes read.
+fn read_block(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match reader.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
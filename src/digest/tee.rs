@@ -0,0 +1,87 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Algorithm, Digest, MultiContext};
+use crate::error;
+use alloc::vec::Vec;
+use std::io;
+
+extern crate std;
+
+/// A writer that forwards writes to an inner writer while calculating the
+/// digests of the written bytes with several algorithms.
+///
+/// Only the bytes that the inner writer accepts are hashed, so the digests
+/// always match what was written, even after short writes or errors.
+///
+/// # Examples
+///
+/// ```
+/// use ring::digest;
+/// use std::io::Write;
+///
+/// let mut writer = digest::TeeHashingWriter::new(
+///     Vec::new(),
+///     &[&digest::SHA256, &digest::SHA512],
+/// )
+/// .unwrap();
+/// writer.write_all(b"hello, world").unwrap();
+/// let (written, digests) = writer.finish();
+/// assert_eq!(written, b"hello, world");
+/// assert_eq!(digests[0].as_ref(), digest::digest(&digest::SHA256, b"hello, world").as_ref());
+/// assert_eq!(digests[1].as_ref(), digest::digest(&digest::SHA512, b"hello, world").as_ref());
+/// ```
+pub struct TeeHashingWriter<W> {
+    inner: W,
+    contexts: MultiContext,
+}
+
+impl<W: io::Write> TeeHashingWriter<W> {
+    /// Constructs a writer that forwards to `inner` and uses each of
+    /// `algorithms`.
+    ///
+    /// Fails if `algorithms` contains any algorithm more than once.
+    pub fn new(inner: W, algorithms: &[&'static Algorithm]) -> Result<Self, error::Unspecified> {
+        Ok(Self {
+            inner,
+            contexts: MultiContext::new(algorithms)?,
+        })
+    }
+
+    /// The inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Finalizes the digest calculations and returns the inner writer and
+    /// the digest values, in the same order as the algorithms were given to
+    /// [`Self::new`].
+    ///
+    /// The inner writer isn't flushed.
+    pub fn finish(self) -> (W, Vec<Digest>) {
+        (self.inner, self.contexts.finish())
+    }
+}
+
+impl<W: io::Write> io::Write for TeeHashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.contexts.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
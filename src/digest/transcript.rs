@@ -0,0 +1,92 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::{Algorithm, Context, Digest};
+use crate::polyfill;
+
+/// A transcript of labeled messages from which challenges can be derived,
+/// e.g. for the Fiat-Shamir transform.
+///
+/// The transcript is a single running digest over a sequence of framed
+/// records. Every record starts with a one-byte record type; every variable
+/// length field is preceded by its length in bytes as a big-endian `u64`:
+///
+/// * `Transcript::new(algorithm, domain)` absorbs `0x00 || len(domain) || domain`.
+/// * `append(label, msg)` absorbs `0x01 || len(label) || label || len(msg) || msg`.
+/// * `challenge(label)` absorbs `0x02 || len(label) || label` and then returns
+///   the digest of everything absorbed so far, without ending the transcript.
+///
+/// Since the challenge record is absorbed before the digest is taken, each
+/// challenge depends on all previous appends and challenges.
+///
+/// # Examples
+///
+/// ```
+/// use ring::digest;
+///
+/// let mut transcript = digest::Transcript::new(&digest::SHA256, b"example protocol");
+/// transcript.append(b"commitment", b"...");
+/// let c1 = transcript.challenge(b"c");
+/// transcript.append(b"response", b"...");
+/// let c2 = transcript.challenge(b"c");
+/// assert_ne!(c1.as_ref(), c2.as_ref());
+/// ```
+#[derive(Clone)]
+pub struct Transcript {
+    ctx: Context,
+}
+
+const RECORD_TYPE_DOMAIN: u8 = 0x00;
+const RECORD_TYPE_APPEND: u8 = 0x01;
+const RECORD_TYPE_CHALLENGE: u8 = 0x02;
+
+impl Transcript {
+    /// Starts a new transcript for the protocol identified by `domain`.
+    pub fn new(algorithm: &'static Algorithm, domain: &[u8]) -> Self {
+        let mut transcript = Self {
+            ctx: Context::new(algorithm),
+        };
+        transcript.ctx.update(&[RECORD_TYPE_DOMAIN]);
+        transcript.absorb_framed(domain);
+        transcript
+    }
+
+    /// Appends the message `msg` with the label `label`.
+    pub fn append(&mut self, label: &[u8], msg: &[u8]) {
+        self.ctx.update(&[RECORD_TYPE_APPEND]);
+        self.absorb_framed(label);
+        self.absorb_framed(msg);
+    }
+
+    /// Derives a challenge with the label `label` from the transcript.
+    ///
+    /// The transcript can continue to be used afterwards.
+    pub fn challenge(&mut self, label: &[u8]) -> Digest {
+        self.ctx.update(&[RECORD_TYPE_CHALLENGE]);
+        self.absorb_framed(label);
+        self.ctx.clone().finish()
+    }
+
+    /// The algorithm that this transcript is using.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.ctx.algorithm()
+    }
+
+    fn absorb_framed(&mut self, value: &[u8]) {
+        self.ctx
+            .update(&polyfill::u64_from_usize(value.len()).to_be_bytes());
+        self.ctx.update(value);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Merkle tree hashing of large inputs.
+//!
+//! [`hash`] splits the input into chunks of `chunk_len` bytes, the last of
+//! which may be shorter, and computes the root of a binary Merkle tree over
+//! them with the construction of [RFC 6962, Section 2.1]:
+//!
+//! * A leaf is `H(0x00 || chunk)`. An empty input is a single empty chunk.
+//! * An interior node over `n > 1` chunks is `H(0x01 || left || right)`,
+//!   where `left` is the root of the first `k` chunks, `k` being the largest
+//!   power of two less than `n`, and `right` is the root of the rest.
+//!
+//! The prefixes keep a leaf from ever having the same digest as an interior
+//! node. The root depends on `chunk_len`, so it must be fixed along with the
+//! algorithm by whoever defines the format.
+//!
+//! With the `rayon` feature, the two subtrees of each node are hashed in
+//! parallel with `rayon::join`. The root doesn't depend on how the work is
+//! scheduled.
+//!
+//! [RFC 6962, Section 2.1]: https://www.rfc-editor.org/rfc/rfc6962#section-2.1
+
+use super::{Algorithm, Context, Digest};
+use crate::error;
+
+/// The prefix of the input of a leaf hash.
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of the input of an interior node hash.
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// Computes the Merkle tree root of `data` split into `chunk_len`-byte
+/// chunks, as described in the [module documentation](self).
+///
+/// Fails if `chunk_len` is zero.
+pub fn hash(
+    algorithm: &'static Algorithm,
+    data: &[u8],
+    chunk_len: usize,
+) -> Result<Digest, error::Unspecified> {
+    if chunk_len == 0 {
+        return Err(error::Unspecified);
+    }
+    Ok(subtree(algorithm, data, chunk_len))
+}
+
+fn subtree(algorithm: &'static Algorithm, data: &[u8], chunk_len: usize) -> Digest {
+    if data.len() <= chunk_len {
+        let mut ctx = Context::new(algorithm);
+        ctx.update(&[LEAF_PREFIX]);
+        ctx.update(data);
+        return ctx.finish();
+    }
+
+    let num_chunks = (data.len() + chunk_len - 1) / chunk_len;
+    let (left, right) = data.split_at(largest_power_of_two_below(num_chunks) * chunk_len);
+    let (left, right) = join(
+        || subtree(algorithm, left, chunk_len),
+        || subtree(algorithm, right, chunk_len),
+    );
+
+    let mut ctx = Context::new(algorithm);
+    ctx.update(&[NODE_PREFIX]);
+    ctx.update(left.as_ref());
+    ctx.update(right.as_ref());
+    ctx.finish()
+}
+
+// `n > 1`.
+fn largest_power_of_two_below(n: usize) -> usize {
+    let k = (n - 1).next_power_of_two();
+    if k == n - 1 {
+        k
+    } else {
+        k / 2
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn join(a: impl FnOnce() -> Digest + Send, b: impl FnOnce() -> Digest + Send) -> (Digest, Digest) {
+    rayon::join(a, b)
+}
+
+#[cfg(not(feature = "rayon"))]
+fn join(a: impl FnOnce() -> Digest, b: impl FnOnce() -> Digest) -> (Digest, Digest) {
+    (a(), b())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_power_of_two_below_n() {
+        for (n, k) in [(2, 1), (3, 2), (4, 2), (5, 4), (8, 4), (9, 8), (1000, 512)] {
+            assert_eq!(largest_power_of_two_below(n), k, "{}", n);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use super::Context;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+impl Context {
+    /// Removes the last `n` bytes of input, if they are still buffered.
+    ///
+    /// Input is buffered until it completes a block, and then the block is
+    /// absorbed into the chaining state. Only input that hasn't been
+    /// absorbed yet can be removed, so this is for undoing a small, recent
+    /// update, e.g. in a speculative parser. The number of bytes that can be
+    /// removed depends on how the input aligns with the block length, and
+    /// is zero right after an update that completes a block.
+    ///
+    /// Fails, without changing the context, if fewer than `n` bytes are
+    /// buffered, or if the context has been [sealed](Self::seal).
+    pub fn unbuffer(&mut self, n: usize) -> Result<(), CannotUnbuffer> {
+        if self.sealed || n > self.num_pending + usize::from(self.pending_cr) {
+            return Err(CannotUnbuffer);
+        }
+        // A `\r` held back by `update_text` is the last byte of input.
+        let n = if n > 0 && core::mem::take(&mut self.pending_cr) {
+            n - 1
+        } else {
+            n
+        };
+        let num_pending = self.num_pending - n;
+        // Don't leave the removed input behind, e.g. to be serialized.
+        self.pending[num_pending..self.num_pending].fill(0);
+        self.num_pending = num_pending;
+        Ok(())
+    }
+}
+
+/// The input to remove with [`Context::unbuffer`] is no longer buffered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CannotUnbuffer;
+
+impl core::fmt::Display for CannotUnbuffer {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("CannotUnbuffer")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CannotUnbuffer {}
@@ -0,0 +1,120 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Differential testing of SHA-256 against the x86 SHA extensions.
+//!
+//! This computes SHA-256 with `core::arch`'s SHA-NI intrinsics, independently
+//! of the crate's assembly and generic implementations, and checks that the
+//! two agree on random inputs. It only runs when the CPU supports the SHA
+//! extensions.
+
+use super::{digest, sha2::SHA256_K, SHA256};
+use crate::rand::{SecureRandom, SystemRandom};
+use alloc::vec::Vec;
+use core::arch::x86_64::*;
+
+extern crate std;
+
+// The intrinsics are safe to call from a `target_feature` function only
+// as of Rust 1.87, so the `unsafe` blocks are needed for older versions.
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+#[allow(unused_unsafe)]
+unsafe fn compress(state: &mut [u32; 8], blocks: &[u8]) {
+    unsafe {
+        let mask = _mm_set_epi64x(0x0c0d0e0f_08090a0b, 0x04050607_00010203);
+
+        // Rearrange the state from ABCD, EFGH into the ABEF, CDGH order that
+        // `_mm_sha256rnds2_epu32` uses.
+        let tmp = _mm_loadu_si128(state[..4].as_ptr().cast());
+        let mut state1 = _mm_loadu_si128(state[4..].as_ptr().cast());
+        let tmp = _mm_shuffle_epi32(tmp, 0xb1);
+        state1 = _mm_shuffle_epi32(state1, 0x1b);
+        let mut state0 = _mm_alignr_epi8(tmp, state1, 8);
+        state1 = _mm_blend_epi16(state1, tmp, 0xf0);
+
+        for block in blocks.chunks_exact(64) {
+            let (abef, cdgh) = (state0, state1);
+
+            let mut w = [_mm_setzero_si128(); 16];
+            for (w, chunk) in w.iter_mut().zip(block.chunks_exact(16)) {
+                *w = _mm_shuffle_epi8(_mm_loadu_si128(chunk.as_ptr().cast()), mask);
+            }
+            for g in 4..16 {
+                let t = _mm_sha256msg1_epu32(w[g - 4], w[g - 3]);
+                let t = _mm_add_epi32(t, _mm_alignr_epi8(w[g - 1], w[g - 2], 4));
+                w[g] = _mm_sha256msg2_epu32(t, w[g - 1]);
+            }
+
+            for (w, k) in w.iter().zip(SHA256_K.chunks_exact(4)) {
+                let k = _mm_loadu_si128(k.as_ptr().cast());
+                let msg = _mm_add_epi32(*w, k);
+                state1 = _mm_sha256rnds2_epu32(state1, state0, msg);
+                state0 = _mm_sha256rnds2_epu32(state0, state1, _mm_shuffle_epi32(msg, 0x0e));
+            }
+
+            state0 = _mm_add_epi32(state0, abef);
+            state1 = _mm_add_epi32(state1, cdgh);
+        }
+
+        let tmp = _mm_shuffle_epi32(state0, 0x1b);
+        state1 = _mm_shuffle_epi32(state1, 0xb1);
+        state0 = _mm_blend_epi16(tmp, state1, 0xf0);
+        state1 = _mm_alignr_epi8(state1, tmp, 8);
+        _mm_storeu_si128(state[..4].as_mut_ptr().cast(), state0);
+        _mm_storeu_si128(state[4..].as_mut_ptr().cast(), state1);
+    }
+}
+
+fn sha256_ni(message: &[u8]) -> [u8; 32] {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    let len_bits = u64::try_from(message.len()).unwrap() * 8;
+    padded.extend_from_slice(&len_bits.to_be_bytes());
+
+    let mut state = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    // SAFETY: The caller checked that the CPU supports the SHA extensions
+    // and SSE4.1.
+    unsafe { compress(&mut state, &padded) };
+
+    let mut out = [0u8; 32];
+    out.chunks_exact_mut(4)
+        .zip(state)
+        .for_each(|(out, word)| out.copy_from_slice(&word.to_be_bytes()));
+    out
+}
+
+#[test]
+fn sha256_matches_sha_ni() {
+    if !(std::is_x86_feature_detected!("sha") && std::is_x86_feature_detected!("sse4.1")) {
+        return;
+    }
+
+    assert_eq!(&sha256_ni(b"abc")[..], digest(&SHA256, b"abc").as_ref());
+
+    let rng = SystemRandom::new();
+    let mut input = Vec::new();
+    for _ in 0..1000 {
+        let mut len = [0u8; 2];
+        rng.fill(&mut len).unwrap();
+        input.resize(usize::from(u16::from_le_bytes(len) % 1024), 0);
+        rng.fill(&mut input).unwrap();
+        assert_eq!(&sha256_ni(&input)[..], digest(&SHA256, &input).as_ref());
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 Brian Smith.
+//
+// Permission to use, copy, modify, and/or distribute this software for any
+// purpose with or without fee is hereby granted, provided that the above
+// copyright notice and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHORS DISCLAIM ALL WARRANTIES
+// WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+// MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY
+// SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+// WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN ACTION
+// OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF OR IN
+// CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+//! Borrowed, read-only access to a context's state.
+
+use super::{dynstate::DynState, Algorithm, BlockContext, Context, MAX_BLOCK_LEN};
+use core::num::Wrapping;
+
+/// A borrowed view of the state of a [`Context`], for reading it, e.g. to
+/// serialize it, without cloning the context.
+///
+/// The view exposes enough to continue the computation, so treat it as
+/// being as sensitive as the input itself.
+#[derive(Clone, Copy)]
+pub struct StateView<'a> {
+    pub(super) block: &'a BlockContext,
+    pub(super) pending: &'a [u8; MAX_BLOCK_LEN],
+    pub(super) num_pending: usize,
+}
+
+/// The chaining state words of a [`StateView`].
+///
+/// There are always eight words. SHA-1 only uses the first five; the rest
+/// are zero.
+#[derive(Clone, Copy)]
+pub enum StateWords<'a> {
+    /// The 32-bit words of SHA-1 and SHA-256.
+    As32(&'a [Wrapping<u32>]),
+    /// The 64-bit words of SHA-384, SHA-512, and the truncated SHA-512
+    /// variants.
+    As64(&'a [Wrapping<u64>]),
+}
+
+impl Context {
+    /// Returns a view of the state of the context that borrows it rather
+    /// than copying it.
+    pub fn state_view(&self) -> StateView<'_> {
+        StateView {
+            block: &self.block,
+            pending: &self.pending,
+            num_pending: self.num_pending,
+        }
+    }
+}
+
+impl<'a> StateView<'a> {
+    /// The algorithm of the context.
+    #[inline(always)]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.block.algorithm
+    }
+
+    /// The number of bytes processed in complete blocks, excluding the
+    /// buffered input.
+    pub fn completed_bytes(&self) -> u128 {
+        self.block.completed_bytes
+    }
+
+    /// The chaining state words.
+    pub fn state_words(&self) -> StateWords<'a> {
+        match &self.block.state {
+            DynState::As32(state) => StateWords::As32(state),
+            DynState::As64(state) => StateWords::As64(state),
+        }
+    }
+
+    /// The buffered input that doesn't yet fill a block.
+    pub fn pending(&self) -> &'a [u8] {
+        &self.pending[..self.num_pending]
+    }
+}
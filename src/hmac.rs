@@ -323,6 +323,71 @@ impl Context {
     }
 }
 
+/// The state of an HMAC [`Context`], for checkpointing a long-running
+/// computation.
+///
+/// The state includes the key's precomputed inner and outer states, so a
+/// context can be restored with [`Context::deserialize`] without the key.
+/// It is therefore as sensitive as the key: anyone who has it can compute
+/// valid tags for any message that starts with the input absorbed so far.
+#[cfg(feature = "serialize")]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HmacContextData {
+    /// The state of the inner digest, which absorbs the key and the input.
+    pub inner: digest::ContextData,
+    /// The state of the outer digest, which has only absorbed the key.
+    pub outer: digest::ContextData,
+}
+
+#[cfg(feature = "serialize")]
+impl Context {
+    /// Serializes the state of the context.
+    pub fn serialize(&self) -> HmacContextData {
+        // The HMAC algorithms never use a custom digest algorithm.
+        HmacContextData {
+            inner: digest::ContextData::try_from(&self.inner).unwrap(),
+            outer: digest::ContextData::try_from(&digest::Context::clone_from(&self.outer))
+                .unwrap(),
+        }
+    }
+
+    /// Restores a context that was serialized with [`Self::serialize`].
+    ///
+    /// Fails with the error from [`digest::Context::deserialize`] if either
+    /// digest state is invalid, and with `DeserializeError::Malformed` if
+    /// the states aren't those of an HMAC computation, e.g. if their
+    /// algorithms differ or the outer state hasn't absorbed exactly one
+    /// block.
+    pub fn deserialize(data: HmacContextData) -> Result<Self, digest::DeserializeError> {
+        let inner = digest::Context::try_from(data.inner)?;
+        let outer = digest::Context::try_from(data.outer)?;
+
+        let algorithm = inner.algorithm();
+        let key_bits = u128::from(crate::polyfill::u64_from_usize(algorithm.block_len())) * 8;
+        let is_hmac_algorithm = [
+            HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+            HMAC_SHA256,
+            HMAC_SHA384,
+            HMAC_SHA512,
+        ]
+        .iter()
+        .any(|hmac| hmac.digest_algorithm() == algorithm);
+        if !is_hmac_algorithm
+            || outer.algorithm() != algorithm
+            || outer.pending_length_bits() != key_bits
+            || inner.pending_length_bits() < key_bits
+        {
+            return Err(digest::DeserializeError::Malformed);
+        }
+        let outer = outer
+            .block_context()
+            .ok_or(digest::DeserializeError::Malformed)?
+            .clone();
+        Ok(Self { inner, outer })
+    }
+}
+
 /// Calculates the HMAC of `data` using the key `key` in one step.
 ///
 /// Use `Context` to calculate HMACs where the input is in multiple parts.
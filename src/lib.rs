@@ -22,6 +22,14 @@
 //!     <th>Description
 //! <tr><td><code>alloc (default)</code>
 //!     <td>Enable features that require use of the heap, RSA in particular.
+//! <tr><td><code>cbor</code>
+//!     <td>Enable CBOR encoding of <code>digest::ContextData</code> using
+//!         <code>ciborium</code>. Implies <code>serde</code>.
+//! <tr><td><code>derive</code>
+//!     <td>Enable <code>#[derive(digest::CanonicalHash)]</code> for structs.
+//! <tr><td><code>educational</code>
+//!     <td>Expose <code>digest::trace_block256</code>, which records the
+//!         intermediate values of a single SHA-256 block compression.
 //! <tr><td><code>less-safe-getrandom-custom-or-rdrand</code>
 //!     <td>Treat user-provided ("custom") and RDRAND-based <code>getrandom</code>
 //!         implementations as secure random number generators (see
@@ -40,15 +48,36 @@
 //!         on esp-idf despite the liklihood that RNG is not secure.
 //!         This feature only works with <code>os = espidf</code> targets.
 //!         See <a href="https://docs.espressif.com/projects/esp-idf/en/stable/esp32/api-reference/system/random.html">
+//! <tr><td><code>rayon</code>
+//!     <td>Hash the subtrees of <code>digest::tree::hash</code> in parallel
+//!         using <code>rayon</code>. Implies `std`.
+//! <tr><td><code>serialize</code>
+//!     <td>Enable <code>digest::ContextData</code> and
+//!         <code>digest::Context::serialize</code>/<code>deserialize</code>.
+//!         Implies `alloc`; the rest of <code>digest</code> does not need the
+//!         heap.
 //! <tr><td><code>std</code>
 //!     <td>Enable features that use libstd, in particular
 //!         <code>std::error::Error</code> integration. Implies `alloc`.
+//! <tr><td><code>tracing</code>
+//!     <td>Emit <code>tracing</code> spans and events, carrying the algorithm
+//!         and the number of bytes, from <code>digest::digest</code>,
+//!         <code>digest::Context::update</code>, and
+//!         <code>digest::Context::finish</code>.
+//! <tr><td><code>verify-intrinsics</code>
+//!     <td>On x86-64, run a test that checks SHA-256 against an
+//!         implementation using the SHA extensions' intrinsics, on CPUs
+//!         that support them. This has no effect outside of tests.
 //! <tr><td><code>wasm32_unknown_unknown_js</code>
 //!     <td>When this feature is enabled, for the wasm32-unknown-unknown target,
 //!         Web APIs will be used to implement features like `ring::rand` that
 //!         require an operating environment of some kind. This has no effect
 //!         for any other target. This enables the `getrandom` crate's `js`
 //!         feature.
+//! <tr><td><code>zeroize</code>
+//!     <td>Overwrite the state and buffered input of <code>digest::Context</code>
+//!         and <code>digest::ContextData</code> with zeros when they are
+//!         dropped, using <code>zeroize</code>.
 //! </table>
 
 // When running mk/package.sh, don't actually build any code.
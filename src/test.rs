@@ -185,6 +185,7 @@ impl TestCase {
             "SHA384" => Some(&digest::SHA384),
             "SHA512" => Some(&digest::SHA512),
             "SHA512_256" => Some(&digest::SHA512_256),
+            "SHA512_224" => Some(&digest::SHA512_224),
             _ => panic!("Unsupported digest algorithm: {}", name),
         }
     }
@@ -180,6 +180,29 @@ test_large_digest!(
     ]
 );
 
+#[test]
+fn digest_sha512_224_known_answers() {
+    // From the NIST examples for SHA-512/224.
+    let one_block = digest::digest(&digest::SHA512_224, b"abc");
+    assert_eq!(
+        one_block.to_hex(),
+        "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa"
+    );
+    let two_block = digest::digest(
+        &digest::SHA512_224,
+        b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn\
+          hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+    );
+    assert_eq!(
+        two_block.to_hex(),
+        "23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9"
+    );
+    assert_eq!(one_block.as_ref().len(), digest::SHA512_224_OUTPUT_LEN);
+    assert_eq!(digest::SHA512_224.output_len(), 224 / 8);
+    assert_eq!(digest::SHA512_224.block_len(), digest::SHA512.block_len());
+    assert_eq!("SHA512_224", &format!("{:?}", digest::SHA512_224));
+}
+
 // TODO: test_large_digest!(digest_test_large_digest_sha512_256,
 //                            digest::SHA512_256, 256 / 8, [ ... ]);
 
@@ -225,3 +248,2218 @@ fn digest_test_fmt() {
         &format!("{:?}", digest::digest(&digest::SHA512_256, b"hello, world"))
     );
 }
+
+#[test]
+fn digest_test_into_finalizer() {
+    type Finalizer = Box<dyn FnOnce(&[u8]) -> digest::Digest>;
+
+    for alg in [
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let mut ctx = digest::Context::new(alg);
+        ctx.update(b"hello");
+        let finalizer: Finalizer = Box::new(ctx.into_finalizer());
+        let actual = finalizer(b", world");
+
+        let mut ctx = digest::Context::new(alg);
+        ctx.update(b"hello");
+        ctx.update(b", world");
+        let expected = ctx.finish();
+
+        assert_eq!(actual.algorithm(), alg);
+        assert_eq!(actual.as_ref(), expected.as_ref());
+    }
+}
+
+#[test]
+fn digest_test_forward_reverse() {
+    for alg in [
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA512,
+    ] {
+        for len in [
+            0,
+            1,
+            alg.block_len() - 1,
+            alg.block_len(),
+            3 * alg.block_len() + 5,
+        ] {
+            let data: Vec<u8> = (0..len).map(|i| (i & 0xff) as u8).collect();
+            let reversed: Vec<u8> = data.iter().rev().copied().collect();
+
+            let (forward, reverse) = digest::digest_forward_reverse(alg, &data);
+            assert_eq!(forward.as_ref(), digest::digest(alg, &data).as_ref());
+            assert_eq!(reverse.as_ref(), digest::digest(alg, &reversed).as_ref());
+        }
+    }
+}
+
+#[test]
+fn digest_test_limited_context() {
+    const LIMIT: usize = 200;
+    let data = [0x5au8; LIMIT + 1];
+
+    let mut ctx = digest::LimitedContext::new(&digest::SHA256, LIMIT as u64);
+    assert_eq!(ctx.update(&data[..100]), Ok(()));
+    assert_eq!(ctx.update(&data[100..LIMIT]), Ok(()));
+    assert_eq!(ctx.update(&data[LIMIT..]), Err(digest::LimitExceeded));
+    assert_eq!(ctx.update(&[]), Ok(()));
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, &data[..LIMIT]).as_ref()
+    );
+
+    let mut ctx = digest::LimitedContext::new(&digest::SHA256, LIMIT as u64);
+    assert_eq!(ctx.update(&data), Err(digest::LimitExceeded));
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, b"").as_ref()
+    );
+}
+
+#[test]
+fn digest_test_records() {
+    let data: Vec<u8> = (0..(7 * 100)).map(|i| (i & 0xff) as u8).collect();
+
+    let digests = digest::digest_records(&digest::SHA256, &data, 100).unwrap();
+    assert_eq!(digests.len(), 7);
+    for (record, actual) in data.chunks(100).zip(&digests) {
+        assert_eq!(
+            actual.as_ref(),
+            digest::digest(&digest::SHA256, record).as_ref()
+        );
+    }
+
+    assert!(digest::digest_records(&digest::SHA256, &[], 100)
+        .unwrap()
+        .is_empty());
+    assert!(digest::digest_records(&digest::SHA256, &data, 99).is_err());
+    assert!(digest::digest_records(&digest::SHA256, &data, 0).is_err());
+}
+
+#[test]
+fn digest_test_finish_truncated() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"hello, world");
+    let full = ctx.clone().finish();
+
+    let truncated = ctx.clone().finish_truncated(16).unwrap();
+    assert_eq!(truncated.algorithm(), &digest::SHA256);
+    assert_eq!(truncated.as_ref(), &full.as_ref()[..16]);
+    assert_eq!(
+        "SHA256/128:09ca7e4eaa6e8ae9c7d2611671291848",
+        &format!("{:?}", truncated)
+    );
+
+    assert_eq!(
+        ctx.clone().finish_truncated(0).unwrap().as_ref(),
+        &[] as &[u8]
+    );
+    assert_eq!(
+        ctx.clone()
+            .finish_truncated(digest::SHA256_OUTPUT_LEN)
+            .unwrap()
+            .as_ref(),
+        full.as_ref()
+    );
+    assert!(ctx.finish_truncated(digest::SHA256_OUTPUT_LEN + 1).is_err());
+}
+
+#[test]
+fn digest_test_set_fingerprint() {
+    let items: Vec<digest::Digest> = [&b"a"[..], b"b", b"c", b"d"]
+        .iter()
+        .map(|item| digest::digest(&digest::SHA256, item))
+        .collect();
+    let mut reordered = items.clone();
+    reordered.reverse();
+    reordered.swap(0, 2);
+
+    let fingerprint = digest::set_fingerprint(&digest::SHA256, &items);
+    assert_eq!(
+        fingerprint.as_ref(),
+        digest::set_fingerprint(&digest::SHA256, &reordered).as_ref()
+    );
+
+    // Duplicates are significant.
+    let mut with_duplicate = items.clone();
+    with_duplicate.push(items[0]);
+    assert_ne!(
+        fingerprint.as_ref(),
+        digest::set_fingerprint(&digest::SHA256, &with_duplicate).as_ref()
+    );
+
+    // A missing item is significant.
+    assert_ne!(
+        fingerprint.as_ref(),
+        digest::set_fingerprint(&digest::SHA256, &items[1..]).as_ref()
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[tracing_test::traced_test]
+#[test]
+fn digest_test_tracing_span() {
+    let _ = digest::digest(&digest::SHA384, b"hello, world");
+    assert!(logs_contain("digest{algorithm=SHA384 len=12}"));
+}
+
+#[test]
+fn digest_test_transcript() {
+    fn run(first_msg: &[u8]) -> (digest::Digest, digest::Digest) {
+        let mut transcript = digest::Transcript::new(&digest::SHA256, b"test protocol");
+        transcript.append(b"first", first_msg);
+        let c1 = transcript.challenge(b"challenge");
+        transcript.append(b"second", b"world");
+        let c2 = transcript.challenge(b"challenge");
+        (c1, c2)
+    }
+
+    // Deterministic.
+    let (a1, a2) = run(b"hello");
+    let (b1, b2) = run(b"hello");
+    assert_eq!(a1.as_ref(), b1.as_ref());
+    assert_eq!(a2.as_ref(), b2.as_ref());
+
+    // Repeated challenges with the same label differ.
+    assert_ne!(a1.as_ref(), a2.as_ref());
+
+    // Challenges depend on all prior appends.
+    let (c1, c2) = run(b"hellO");
+    assert_ne!(a1.as_ref(), c1.as_ref());
+    assert_ne!(a2.as_ref(), c2.as_ref());
+
+    // The framing is as documented.
+    let mut expected = digest::Context::new(&digest::SHA256);
+    expected.update(&[0x00]);
+    expected.update(&13u64.to_be_bytes());
+    expected.update(b"test protocol");
+    expected.update(&[0x01]);
+    expected.update(&5u64.to_be_bytes());
+    expected.update(b"first");
+    expected.update(&5u64.to_be_bytes());
+    expected.update(b"hello");
+    expected.update(&[0x02]);
+    expected.update(&9u64.to_be_bytes());
+    expected.update(b"challenge");
+    assert_eq!(a1.as_ref(), expected.finish().as_ref());
+}
+
+#[test]
+fn digest_test_crc32() {
+    assert_eq!(digest::crc32(b""), 0);
+    assert_eq!(digest::crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(
+        digest::crc32(b"The quick brown fox jumps over the lazy dog"),
+        0x414f_a339
+    );
+}
+
+#[test]
+fn digest_test_crc32c() {
+    // From RFC 3720, Appendix B.4.
+    let incrementing: Vec<u8> = (0..32).collect();
+    let decrementing: Vec<u8> = (0..32).rev().collect();
+    assert_eq!(digest::crc32c(&[0x00; 32]), 0x8a91_36aa);
+    assert_eq!(digest::crc32c(&[0xff; 32]), 0x62a8_ab43);
+    assert_eq!(digest::crc32c(&incrementing), 0x46dd_794e);
+    assert_eq!(digest::crc32c(&decrementing), 0x113f_db5c);
+
+    assert_eq!(digest::crc32c(b""), 0);
+    assert_eq!(digest::crc32c(b"123456789"), 0xe306_9283);
+}
+
+#[test]
+fn digest_test_update_word_slices() {
+    let words32: Vec<u32> = (0..100u32).map(|i| i.wrapping_mul(0x9e37_79b9)).collect();
+    let words64: Vec<u64> = (0..100u64)
+        .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .collect();
+
+    let check = |update: &dyn Fn(&mut digest::Context), bytes: Vec<u8>| {
+        for alg in [&digest::SHA256, &digest::SHA512] {
+            let mut ctx = digest::Context::new(alg);
+            ctx.update(b"prefix");
+            update(&mut ctx);
+            let mut expected = b"prefix".to_vec();
+            expected.extend(&bytes);
+            assert_eq!(
+                ctx.finish().as_ref(),
+                digest::digest(alg, &expected).as_ref()
+            );
+        }
+    };
+
+    check(
+        &|ctx| ctx.update_u32_slice_be(&words32),
+        words32.iter().flat_map(|w| w.to_be_bytes()).collect(),
+    );
+    check(
+        &|ctx| ctx.update_u32_slice_le(&words32),
+        words32.iter().flat_map(|w| w.to_le_bytes()).collect(),
+    );
+    check(
+        &|ctx| ctx.update_u64_slice_be(&words64),
+        words64.iter().flat_map(|w| w.to_be_bytes()).collect(),
+    );
+    check(
+        &|ctx| ctx.update_u64_slice_le(&words64),
+        words64.iter().flat_map(|w| w.to_le_bytes()).collect(),
+    );
+}
+
+#[test]
+fn digest_test_update_f64_slice() {
+    fn hash(values: &[f64]) -> digest::Digest {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update_f64_slice(values);
+        ctx.finish()
+    }
+
+    // Zeros.
+    assert_eq!(hash(&[0.0]).as_ref(), hash(&[-0.0]).as_ref());
+    assert_eq!(
+        hash(&[-0.0]).as_ref(),
+        digest::digest(&digest::SHA256, &[0; 8]).as_ref()
+    );
+
+    // NaNs.
+    let nans = [
+        f64::NAN,
+        -f64::NAN,
+        f64::from_bits(0x7ff0_0000_0000_0001), // Signaling NaN.
+        f64::from_bits(0xfff8_0000_dead_beef),
+    ];
+    for nan in nans {
+        assert!(nan.is_nan());
+        assert_eq!(hash(&[1.0, nan]).as_ref(), hash(&[1.0, f64::NAN]).as_ref());
+    }
+    assert_eq!(
+        hash(&[f64::NAN]).as_ref(),
+        digest::digest(&digest::SHA256, &0x7ff8_0000_0000_0000u64.to_be_bytes()).as_ref()
+    );
+
+    // Other values are hashed as their big-endian bits.
+    let values = [
+        1.5,
+        -2.25,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::MIN_POSITIVE / 2.0,
+    ];
+    let bytes: Vec<u8> = values
+        .iter()
+        .flat_map(|v| v.to_bits().to_be_bytes())
+        .collect();
+    assert_eq!(
+        hash(&values).as_ref(),
+        digest::digest(&digest::SHA256, &bytes).as_ref()
+    );
+    assert_ne!(hash(&[1.0]).as_ref(), hash(&[-1.0]).as_ref());
+}
+
+#[cfg(feature = "educational")]
+#[test]
+fn trace_block256() {
+    const IV: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    // The single padded block of "abc" (FIPS 180-4 example B.1).
+    let mut block = [0u8; 64];
+    block[..4].copy_from_slice(b"abc\x80");
+    block[63] = 0x18;
+
+    let trace = digest::trace_block256(&IV, &block);
+
+    // W0..W15 are the block's big-endian words. With W1..W14 zero,
+    // W16 = σ1(W14) + W9 + σ0(W1) + W0 = W0 and
+    // W17 = σ1(W15) + W10 + σ0(W2) + W1 = σ1(0x18)
+    //     = ROTR17(0x18) ^ ROTR19(0x18) ^ SHR10(0x18) = 0x000c0000 ^ 0x00030000.
+    assert_eq!(trace.schedule[0], 0x61626380);
+    assert!(trace.schedule[1..15].iter().all(|&w| w == 0));
+    assert_eq!(trace.schedule[15], 0x00000018);
+    assert_eq!(trace.schedule[16], 0x61626380);
+    assert_eq!(trace.schedule[17], 0x000f0000);
+
+    assert_eq!(
+        trace.rounds[0],
+        [
+            0x5d6aebcd, 0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xfa2a4622, 0x510e527f, 0x9b05688c,
+            0x1f83d9ab
+        ]
+    );
+
+    let expected: Vec<u8> = trace.state.iter().flat_map(|w| w.to_be_bytes()).collect();
+    assert_eq!(
+        digest::digest(&digest::SHA256, b"abc").as_ref(),
+        &expected[..]
+    );
+    for (i, (out, iv)) in trace.state.iter().zip(IV).enumerate() {
+        assert_eq!(*out, iv.wrapping_add(trace.rounds[63][i]));
+    }
+}
+
+#[test]
+fn digest_build_hasher() {
+    use std::{
+        collections::HashMap,
+        hash::{BuildHasher, Hasher},
+    };
+
+    let build = digest::DigestBuildHasher::new(&digest::SHA256);
+    let mut map = HashMap::with_hasher(build);
+    for i in 0..1000u32 {
+        assert_eq!(map.insert(i, i * 2), None);
+    }
+    assert_eq!(map.len(), 1000);
+    for i in 0..1000u32 {
+        assert_eq!(map.get(&i), Some(&(i * 2)));
+    }
+    assert_eq!(map.get(&1000), None);
+
+    // The hash is the leading 64 bits of the digest of the written bytes.
+    let mut hasher = build.build_hasher();
+    hasher.write(b"abc");
+    let expected = digest::digest(&digest::SHA256, b"abc");
+    let mut leading = [0u8; 8];
+    leading.copy_from_slice(&expected.as_ref()[..8]);
+    assert_eq!(hasher.finish(), u64::from_be_bytes(leading));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn digest_receiver() {
+    let chunks: Vec<Vec<u8>> = [0usize, 1, 63, 64, 65, 0, 1000]
+        .iter()
+        .enumerate()
+        .map(|(i, &len)| vec![i.to_le_bytes()[0]; len])
+        .collect();
+    let expected = chunks.concat();
+
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let to_send = chunks.clone();
+        let producer = std::thread::spawn(move || {
+            to_send
+                .into_iter()
+                .for_each(|chunk| tx.send(chunk).unwrap());
+        });
+        let actual = digest::digest_receiver(alg, rx);
+        producer.join().unwrap();
+        assert_eq!(actual.as_ref(), digest::digest(alg, &expected).as_ref());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn demux_hasher() {
+    let records: [(u32, &[u8]); 6] = [
+        (7, b"alpha"),
+        (3, &[0x5a; 100]),
+        (7, b""),
+        (7, &[0xa5; 200]),
+        (3, b"beta"),
+        (7, b"gamma"),
+    ];
+
+    let mut demux = digest::DemuxHasher::new(&digest::SHA256);
+    records
+        .iter()
+        .for_each(|&(stream_id, data)| demux.feed(stream_id, data));
+    let digests = demux.finish();
+
+    assert_eq!(digests.len(), 2);
+    for stream_id in [3, 7] {
+        let stream: Vec<u8> = records
+            .iter()
+            .filter(|&&(id, _)| id == stream_id)
+            .flat_map(|&(_, data)| data.iter().copied())
+            .collect();
+        assert_eq!(
+            digests[&stream_id].as_ref(),
+            digest::digest(&digest::SHA256, &stream).as_ref()
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn file_block_signatures() {
+    let contents: Vec<u8> = (0..10_000u32).map(|i| i.to_le_bytes()[0]).collect();
+    let path =
+        std::env::temp_dir().join(format!("ring-file-block-signatures-{}", std::process::id()));
+
+    for len in [0, 1, 4096, 8192, 10_000] {
+        std::fs::write(&path, &contents[..len]).unwrap();
+        for block_size in [1000, 4096] {
+            let signatures =
+                digest::file_block_signatures(&digest::SHA256, &path, block_size).unwrap();
+            let expected: Vec<digest::Digest> = contents[..len]
+                .chunks(block_size)
+                .map(|block| digest::digest(&digest::SHA256, block))
+                .collect();
+            assert_eq!(signatures.len(), expected.len());
+            for (actual, expected) in signatures.iter().zip(&expected) {
+                assert_eq!(actual.as_ref(), expected.as_ref());
+            }
+        }
+    }
+
+    assert_eq!(
+        digest::file_block_signatures(&digest::SHA256, &path, 0)
+            .unwrap_err()
+            .kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert!(digest::file_block_signatures(&digest::SHA256, &path, 4096).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn first_differing_block() {
+    const BLOCK_SIZE: usize = 1024;
+    let contents: Vec<u8> = (0..(5 * BLOCK_SIZE + 100))
+        .map(|i: usize| i.to_le_bytes()[0])
+        .collect();
+    let dir = std::env::temp_dir();
+    let path_a = dir.join(format!(
+        "ring-first-differing-block-a-{}",
+        std::process::id()
+    ));
+    let path_b = dir.join(format!(
+        "ring-first-differing-block-b-{}",
+        std::process::id()
+    ));
+    let first_differing_block = |a: &[u8], b: &[u8]| {
+        std::fs::write(&path_a, a).unwrap();
+        std::fs::write(&path_b, b).unwrap();
+        digest::first_differing_block(&digest::SHA256, &path_a, &path_b, BLOCK_SIZE).unwrap()
+    };
+
+    assert_eq!(first_differing_block(&contents, &contents), None);
+    assert_eq!(first_differing_block(b"", b""), None);
+
+    let mut changed = contents.clone();
+    changed[2 * BLOCK_SIZE + 10] ^= 1;
+    assert_eq!(first_differing_block(&contents, &changed), Some(2));
+    assert_eq!(first_differing_block(&changed, &contents), Some(2));
+
+    assert_eq!(
+        first_differing_block(&contents, &contents[..(3 * BLOCK_SIZE)]),
+        Some(3)
+    );
+    assert_eq!(
+        first_differing_block(&contents[..(3 * BLOCK_SIZE + 1)], &contents),
+        Some(3)
+    );
+    assert_eq!(first_differing_block(b"", &contents), Some(0));
+
+    assert_eq!(
+        digest::first_differing_block(&digest::SHA256, &path_a, &path_b, 0)
+            .unwrap_err()
+            .kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+    std::fs::remove_file(&path_a).unwrap();
+    std::fs::remove_file(&path_b).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn digest_auto() {
+    let data: Vec<Vec<u8>> = (0..500u32)
+        .map(|i| (0..i).map(|j| (i ^ j).to_le_bytes()[0]).collect())
+        .collect();
+    for num_inputs in [0, 1, 31, 32, 33, 500] {
+        let inputs: Vec<&[u8]> = data[..num_inputs].iter().map(|v| &v[..]).collect();
+        for &alg in &[
+            &digest::SHA1_FOR_LEGACY_USE_ONLY,
+            &digest::SHA256,
+            &digest::SHA512,
+        ] {
+            let actual = digest::digest_auto(alg, &inputs);
+            assert_eq!(actual.len(), inputs.len());
+            for (actual, input) in actual.iter().zip(&inputs) {
+                assert_eq!(actual.as_ref(), digest::digest(alg, input).as_ref());
+            }
+        }
+    }
+}
+
+#[test]
+fn context_state_width() {
+    use digest::StateWidth;
+
+    for (alg, expected) in [
+        (&digest::SHA1_FOR_LEGACY_USE_ONLY, StateWidth::Bits32),
+        (&digest::SHA256, StateWidth::Bits32),
+        (&digest::SHA384, StateWidth::Bits64),
+        (&digest::SHA512, StateWidth::Bits64),
+        (&digest::SHA512_256, StateWidth::Bits64),
+    ] {
+        let mut ctx = digest::Context::new(alg);
+        assert_eq!(ctx.state_width(), expected);
+        ctx.update(&[0u8; 300]);
+        assert_eq!(ctx.state_width(), expected);
+    }
+}
+
+#[test]
+fn context_offset_in_block() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let block_len = alg.block_len();
+        let mut ctx = digest::Context::new(alg);
+        assert_eq!(ctx.offset_in_block(), 0);
+
+        let mut total = 0;
+        for len in [1, 0, block_len - 2, 1, 3, block_len, 2 * block_len + 5] {
+            ctx.update(&vec![0u8; len]);
+            total += len;
+            assert_eq!(ctx.offset_in_block(), total % block_len);
+        }
+
+        // Padding up to the boundary returns the offset to zero.
+        ctx.update(&vec![0u8; block_len - ctx.offset_in_block()]);
+        assert_eq!(ctx.offset_in_block(), 0);
+    }
+}
+
+#[test]
+fn digest_hex_known_answer() {
+    let digest = digest::digest(&digest::SHA256, b"hello, world");
+    assert_eq!(
+        digest.to_hex(),
+        "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b"
+    );
+    assert_eq!(
+        digest.to_hex_upper(),
+        "09CA7E4EAA6E8AE9C7D261167129184883644D07DFBA7CBFBC4C8A2E08360D5B"
+    );
+}
+
+#[test]
+fn digest_hex() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        for len in [0, 1, 100] {
+            let digest = digest::digest(alg, &vec![0xa5u8; len]);
+            let expected: String = digest
+                .as_ref()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+
+            assert_eq!(digest.to_hex(), expected);
+            assert_eq!(digest.to_hex_upper(), expected.to_uppercase());
+            assert_eq!(digest.to_string(), expected);
+            let mut written = String::new();
+            digest.write_hex(&mut written).unwrap();
+            assert_eq!(written, expected);
+            assert_eq!(format!("{:?}", digest), format!("{:?}:{}", alg, expected));
+            assert_eq!(test::from_hex(&expected).unwrap(), digest.as_ref());
+        }
+    }
+}
+
+#[test]
+fn digest_uri() {
+    // SHA-256("abc"), base32-encoded.
+    let digest = digest::digest(&digest::SHA256, b"abc");
+    let uri = digest.to_uri();
+    assert_eq!(
+        uri,
+        "sha256:xj4bnp4pahh6uqkbidpf3lrceoyagyndsylxvhfucd7wd4qacwwq"
+    );
+
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let digest = digest::digest(alg, b"abc");
+        let parsed = digest::Digest::from_uri(&digest.to_uri()).unwrap();
+        assert_eq!(parsed.algorithm(), alg);
+        assert_eq!(parsed.as_ref(), digest.as_ref());
+    }
+    assert!(digest::digest(&digest::SHA512_256, b"")
+        .to_uri()
+        .starts_with("sha512_256:"));
+
+    let value = &uri["sha256:".len()..];
+    let bad = [
+        // Bad prefixes.
+        format!("SHA256:{}", value),
+        format!("sha-256:{}", value),
+        format!("md5:{}", value),
+        format!("custom_sha256:{}", value),
+        value.to_string(),
+        // Bad lengths.
+        format!("sha512:{}", value),
+        format!("sha256:{}", &value[..(value.len() - 1)]),
+        format!("sha256:{}a", value),
+        "sha256:".to_string(),
+        // Not canonical lowercase base32.
+        format!("sha256:{}", value.to_uppercase()),
+        format!("sha256:{}====", value),
+        format!("sha256:{}r", &value[..(value.len() - 1)]),
+    ];
+    for uri in bad {
+        assert!(digest::Digest::from_uri(&uri).is_err(), "{}", uri);
+    }
+}
+
+#[test]
+fn algorithm_id_names() {
+    use digest::AlgorithmId;
+
+    // These are the names that stored contexts used before `AlgorithmId`.
+    for (id, name) in [
+        (AlgorithmId::SHA1, "SHA1"),
+        (AlgorithmId::SHA256, "SHA256"),
+        (AlgorithmId::SHA384, "SHA384"),
+        (AlgorithmId::SHA512, "SHA512"),
+        (AlgorithmId::SHA512_256, "SHA512_256"),
+        (AlgorithmId::SHA512_224, "SHA512_224"),
+    ] {
+        assert_eq!(id.to_string(), name);
+        assert_eq!(name.parse::<AlgorithmId>(), Ok(id));
+    }
+
+    // Custom algorithms have no id.
+    assert!("CUSTOM_SHA256".parse::<AlgorithmId>().is_err());
+    assert!("SHA-256".parse::<AlgorithmId>().is_err());
+    assert!("sha256".parse::<AlgorithmId>().is_err());
+    assert!("".parse::<AlgorithmId>().is_err());
+}
+
+#[test]
+fn algorithm_from_name() {
+    use digest::{Algorithm, AlgorithmId};
+
+    for (name, alg) in [
+        ("SHA1", &digest::SHA1_FOR_LEGACY_USE_ONLY),
+        ("SHA256", &digest::SHA256),
+        ("SHA384", &digest::SHA384),
+        ("SHA512", &digest::SHA512),
+        ("SHA512_256", &digest::SHA512_256),
+        ("SHA512_224", &digest::SHA512_224),
+    ] {
+        assert_eq!(Algorithm::from_name(name), Some(alg));
+        let id = name.parse::<AlgorithmId>().unwrap();
+        assert_eq!(Algorithm::from_id(id), alg);
+    }
+
+    for name in ["CUSTOM_SHA256", "CUSTOM_SHA512", "SHA-256", "MD5", ""] {
+        assert_eq!(Algorithm::from_name(name), None, "{}", name);
+    }
+}
+
+#[test]
+fn context_len() {
+    let data = [0x5au8; 1000];
+    for alg in [&digest::SHA256, &digest::SHA512] {
+        let mut ctx = digest::Context::new(alg);
+        assert!(ctx.is_empty());
+        assert_eq!(ctx.len(), 0);
+        assert!(!ctx.would_overflow(usize::MAX / 8));
+
+        let mut total = 0;
+        for chunk_len in [0, 1, 63, 64, 65, 127, 128, 300, 0, 1] {
+            ctx.update(&data[..chunk_len]);
+            total += chunk_len;
+            assert_eq!(ctx.len(), total as u64);
+            assert_eq!(ctx.is_empty(), total == 0);
+        }
+        assert_eq!(ctx.finish_with_len().1, total as u64);
+    }
+}
+
+#[test]
+fn context_finish_verify() {
+    use digest::VerificationError;
+
+    let stream: Vec<&[u8]> = vec![b"hello, ", &[0x5a; 200], b"world"];
+    let expected = digest::digest(&digest::SHA256, &stream.concat());
+    let hash_stream = |stream: &[&[u8]]| {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        stream.iter().for_each(|chunk| ctx.update(chunk));
+        ctx
+    };
+
+    assert_eq!(
+        hash_stream(&stream).finish_verify(expected.as_ref()),
+        Ok(())
+    );
+
+    let mut flipped = stream[1].to_vec();
+    flipped[100] ^= 0x01;
+    assert_eq!(
+        hash_stream(&[stream[0], &flipped, stream[2]]).finish_verify(expected.as_ref()),
+        Err(VerificationError::Mismatch)
+    );
+
+    for len in [0, 31, 33, 64] {
+        let mut wrong_len = expected.as_ref().to_vec();
+        wrong_len.resize(len, 0);
+        assert_eq!(
+            hash_stream(&stream).finish_verify(&wrong_len),
+            Err(VerificationError::WrongLength {
+                expected: 32,
+                actual: len
+            })
+        );
+    }
+}
+
+#[test]
+fn digest_truncated() {
+    for alg in [&digest::SHA256, &digest::SHA384] {
+        let digest = digest::digest(alg, b"abc");
+        let len = alg.output_len();
+        for prefix_len in [0, 1, 16, len] {
+            assert_eq!(
+                digest.truncated(prefix_len),
+                Ok(&digest.as_ref()[..prefix_len])
+            );
+        }
+        assert_eq!(digest.truncated(len + 1), Err(digest::TruncationError));
+        assert_eq!(
+            digest::DigestError::from(digest.truncated(usize::MAX).unwrap_err()),
+            digest::DigestError::Truncation
+        );
+    }
+}
+
+#[test]
+fn digest_xor_fold() {
+    // SHA-256("abc") = ba7816bf8f01cfea414140de5dae2223 b00361a396177a9cb410ff61f20015ad
+    let digest = digest::digest(&digest::SHA256, b"abc");
+
+    // The two halves XORed together.
+    assert_eq!(
+        digest.xor_fold(16).unwrap(),
+        test::from_hex("0a7b771c1916b576f551bfbfafae378e").unwrap()
+    );
+    // Two full 12-byte rows and a partial 8-byte row.
+    assert_eq!(
+        digest.xor_fold(12).unwrap(),
+        test::from_hex("53c6cbfdcd02bbe4d7563a42").unwrap()
+    );
+    assert_eq!(digest.xor_fold(32).unwrap(), digest.as_ref());
+
+    assert!(digest.xor_fold(0).is_err());
+    assert!(digest.xor_fold(33).is_err());
+}
+
+#[test]
+fn digest_with_hole() {
+    use digest::HoleMode;
+
+    let data: Vec<u8> = (0..500u32).map(|i| i.to_le_bytes()[0] | 1).collect();
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        for hole in [0..0, 0..10, 10..10, 10..20, 100..400, 490..500, 0..500] {
+            let mut omitted = data[..hole.start].to_vec();
+            omitted.extend_from_slice(&data[hole.end..]);
+            assert_eq!(
+                digest::digest_with_hole(alg, &data, hole.clone(), HoleMode::Omit)
+                    .unwrap()
+                    .as_ref(),
+                digest::digest(alg, &omitted).as_ref()
+            );
+
+            let mut zeroed = data.clone();
+            zeroed[hole.clone()].iter_mut().for_each(|b| *b = 0);
+            assert_eq!(
+                digest::digest_with_hole(alg, &data, hole.clone(), HoleMode::ZeroFill)
+                    .unwrap()
+                    .as_ref(),
+                digest::digest(alg, &zeroed).as_ref()
+            );
+        }
+
+        #[allow(clippy::reversed_empty_ranges)]
+        for hole in [0..501, 500..501, 501..501, 20..10] {
+            for mode in [HoleMode::Omit, HoleMode::ZeroFill] {
+                assert!(digest::digest_with_hole(alg, &data, hole.clone(), mode).is_err());
+            }
+        }
+    }
+}
+
+#[test]
+fn digest_path() {
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        let path = digest::digest_path(alg, &[1, 2]);
+        assert_eq!(path.as_ref(), digest::digest_path(alg, &[1, 2]).as_ref());
+        assert_ne!(path.as_ref(), digest::digest_path(alg, &[1, 2, 0]).as_ref());
+        assert_ne!(path.as_ref(), digest::digest_path(alg, &[2, 1]).as_ref());
+        assert_ne!(
+            path.as_ref(),
+            digest::digest_path(alg, &[0x0001_0002]).as_ref()
+        );
+        assert_ne!(path.as_ref(), digest::digest_path(alg, &[]).as_ref());
+
+        // The path is domain separated from the plain encoding.
+        assert_ne!(
+            path.as_ref(),
+            digest::digest(alg, &[0, 0, 0, 1, 0, 0, 0, 2]).as_ref()
+        );
+    }
+}
+
+#[test]
+fn digest_verify() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let expected = digest::digest(alg, b"data");
+        assert!(digest::verify(alg, b"data", expected.as_ref()));
+        assert!(!digest::verify(alg, b"datb", expected.as_ref()));
+
+        let mut flipped = expected.as_ref().to_vec();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(!digest::verify(alg, b"data", &flipped));
+
+        let value = expected.as_ref();
+        assert!(!digest::verify(alg, b"data", &value[..(value.len() - 1)]));
+        assert!(!digest::verify(alg, b"data", &[value, &[0]].concat()));
+        assert!(!digest::verify(alg, b"data", &[]));
+    }
+}
+
+#[test]
+fn digest_repeated() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+    ] {
+        let block_len = alg.block_len();
+        assert_eq!(
+            digest::digest_repeated(alg, &[0], u64::try_from(block_len * 3).unwrap()).as_ref(),
+            digest::digest(alg, &vec![0u8; block_len * 3]).as_ref()
+        );
+
+        for pattern in [&b""[..], b"\xaa", b"abc", &[0x5a; 200], &[0xa5; 5000]] {
+            for count in [0u64, 1, 2, 100, 5000] {
+                let expected = pattern.repeat(usize::try_from(count).unwrap());
+                assert_eq!(
+                    digest::digest_repeated(alg, pattern, count).as_ref(),
+                    digest::digest(alg, &expected).as_ref()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn digest_len_suffixed() {
+    // SHA-256("abc" || 00 00 00 00 00 00 00 03)
+    assert_eq!(
+        digest::digest_len_suffixed(&digest::SHA256, b"abc").as_ref(),
+        test::from_hex("b74ffbd3cb2a7ef1b7af06ef43ff7400fea78948dd7d10e226fbd1f4bb0737db").unwrap()
+    );
+
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        let message = [0x5au8; 200];
+        let mut suffixed = message.to_vec();
+        suffixed.extend_from_slice(&200u64.to_be_bytes());
+        assert_eq!(
+            digest::digest_len_suffixed(alg, &message).as_ref(),
+            digest::digest(alg, &suffixed).as_ref()
+        );
+    }
+}
+
+#[test]
+fn digest_rope() {
+    use std::{rc::Rc, sync::Arc};
+
+    let text: Vec<u8> = (0..1000u32)
+        .map(|i| b'a' + (i % 26).to_le_bytes()[0])
+        .collect();
+    let split_points = [0, 1, 1, 63, 64, 65, 200, 999, 1000];
+    let pieces: Vec<&[u8]> = split_points.windows(2).map(|w| &text[w[0]..w[1]]).collect();
+
+    for &alg in &[&digest::SHA256, &digest::SHA384] {
+        let expected = digest::digest(alg, &text);
+
+        let arcs: Vec<Arc<[u8]>> = pieces.iter().map(|&p| Arc::from(p)).collect();
+        assert_eq!(digest::digest_rope(alg, &arcs).as_ref(), expected.as_ref());
+        assert_eq!(
+            digest::digest_rope(alg, arcs.iter().cloned()).as_ref(),
+            expected.as_ref()
+        );
+
+        let rcs: Vec<Rc<[u8]>> = pieces.iter().map(|&p| Rc::from(p)).collect();
+        assert_eq!(digest::digest_rope(alg, rcs).as_ref(), expected.as_ref());
+
+        assert_eq!(
+            digest::digest_rope(alg, core::iter::empty::<&[u8]>()).as_ref(),
+            digest::digest(alg, b"").as_ref()
+        );
+    }
+}
+
+#[test]
+fn context_will_match() {
+    let data = [0x3cu8; 300];
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512_256,
+    ] {
+        let mut a = digest::Context::new(alg);
+        let mut b = digest::Context::new(alg);
+        assert!(a.will_match(&b));
+
+        // Same input, chunked differently.
+        a.update(&data);
+        b.update(&data[..1]);
+        b.update(&data[1..alg.block_len() + 7]);
+        b.update(&data[alg.block_len() + 7..]);
+        assert!(a.will_match(&b));
+        assert!(b.will_match(&a));
+
+        // Checking doesn't consume or disturb either context.
+        let expected = digest::digest(alg, &data);
+        assert_eq!(a.clone().finish().as_ref(), expected.as_ref());
+
+        b.update(b"x");
+        assert!(!a.will_match(&b));
+        a.update(b"y");
+        assert!(!a.will_match(&b));
+    }
+
+    // SHA-512/256 is a truncation of SHA-512 with a different initial state;
+    // contexts using different algorithms never match.
+    let mut a = digest::Context::new(&digest::SHA512_256);
+    let mut b = digest::Context::new(&digest::SHA512);
+    a.update(&data);
+    b.update(&data);
+    assert!(!a.will_match(&b));
+}
+
+#[test]
+fn context_midstate_hex() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    assert_eq!(
+        ctx.midstate_hex().unwrap(),
+        "6a09e667bb67ae853c6ef372a54ff53a510e527f9b05688c1f83d9ab5be0cd19"
+    );
+
+    // Computed with an independent implementation of the SHA-256
+    // compression function.
+    const MIDSTATE_64_A: &str = "df5bb81ce81e0626fb45a8944fd40f31b25e6816d6d499c1ab90492900635e66";
+    ctx.update(&[b'a'; 64]);
+    assert_eq!(ctx.midstate_hex().unwrap(), MIDSTATE_64_A);
+
+    // Buffered input doesn't affect the midstate until the block is full.
+    ctx.update(b"partial");
+    assert_eq!(ctx.midstate_hex().unwrap(), MIDSTATE_64_A);
+
+    let mut ctx = digest::Context::new(&digest::SHA1_FOR_LEGACY_USE_ONLY);
+    assert_eq!(
+        ctx.midstate_hex().unwrap(),
+        "67452301efcdab8998badcfe10325476c3d2e1f0"
+    );
+    ctx.update(&[0u8; 64]);
+    assert_eq!(ctx.midstate_hex().unwrap().len(), 40);
+
+    assert_eq!(
+        digest::Context::new(&digest::SHA512)
+            .midstate_hex()
+            .unwrap()
+            .len(),
+        128
+    );
+
+    for &alg in &[&digest::SHA384, &digest::SHA512_256] {
+        assert!(digest::Context::new(alg).midstate_hex().is_err());
+    }
+}
+
+#[test]
+fn digest_result_conversions() {
+    use digest::{DigestError, DigestResult};
+
+    fn limited(data: &[u8]) -> DigestResult<()> {
+        let mut ctx = digest::LimitedContext::new(&digest::SHA256, 4);
+        ctx.update(data)?;
+        Ok(())
+    }
+
+    fn truncated(len: usize) -> DigestResult<usize> {
+        let ctx = digest::Context::new(&digest::SHA256);
+        Ok(ctx.finish_truncated(len)?.as_ref().len())
+    }
+
+    assert_eq!(limited(b"1234"), Ok(()));
+    assert_eq!(limited(b"12345"), Err(DigestError::LimitExceeded));
+    assert_eq!(truncated(16), Ok(16));
+    assert_eq!(truncated(33), Err(DigestError::Unspecified));
+    assert_eq!(DigestError::LimitExceeded.to_string(), "LimitExceeded");
+
+    fn unbuffered(n: usize) -> DigestResult<()> {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(b"abc");
+        ctx.unbuffer(n)?;
+        Ok(())
+    }
+
+    assert_eq!(unbuffered(3), Ok(()));
+    assert_eq!(unbuffered(4), Err(DigestError::CannotUnbuffer));
+
+    assert_eq!(
+        digest::Digest::from_hex(&digest::SHA256, "0"),
+        Err(DigestError::ParseDigest(
+            digest::ParseDigestError::OddLength
+        ))
+    );
+}
+
+#[test]
+fn context_derive_subkey() {
+    let mut prefix = digest::Context::new(&digest::SHA256);
+    prefix.update(b"shared prefix");
+
+    let a = prefix.derive_subkey(0, 16).unwrap();
+    let b = prefix.derive_subkey(1, 16).unwrap();
+    assert_eq!(a.as_ref().len(), 16);
+    assert_ne!(a.as_ref(), b.as_ref());
+
+    // Deterministic, and doesn't disturb the prefix.
+    assert_eq!(prefix.derive_subkey(0, 16).unwrap().as_ref(), a.as_ref());
+    assert_eq!(
+        prefix.derive_subkey(1, 32).unwrap().as_ref(),
+        digest::digest(&digest::SHA256, b"shared prefix\x00\x00\x00\x01").as_ref()
+    );
+
+    let outputs: Vec<_> = (0..100)
+        .map(|i| prefix.derive_subkey(i, 32).unwrap().as_ref().to_vec())
+        .collect();
+    for (i, a) in outputs.iter().enumerate() {
+        assert!(outputs[(i + 1)..].iter().all(|b| a != b));
+    }
+
+    assert!(prefix.derive_subkey(0, 33).is_err());
+}
+
+/// Exercises the public surface of upstream *ring* 0.17's `ring::digest`,
+/// which code migrating to this crate relies on being unchanged.
+#[test]
+fn upstream_ring_digest_surface() {
+    use ring::digest::{
+        digest, Algorithm, Context, Digest, MAX_BLOCK_LEN, MAX_CHAINING_LEN, MAX_OUTPUT_LEN,
+        SHA1_FOR_LEGACY_USE_ONLY, SHA1_OUTPUT_LEN, SHA256, SHA256_OUTPUT_LEN, SHA384,
+        SHA384_OUTPUT_LEN, SHA512, SHA512_256, SHA512_256_OUTPUT_LEN, SHA512_OUTPUT_LEN,
+    };
+
+    let algorithms: [(&'static Algorithm, usize); 5] = [
+        (&SHA1_FOR_LEGACY_USE_ONLY, SHA1_OUTPUT_LEN),
+        (&SHA256, SHA256_OUTPUT_LEN),
+        (&SHA384, SHA384_OUTPUT_LEN),
+        (&SHA512, SHA512_OUTPUT_LEN),
+        (&SHA512_256, SHA512_256_OUTPUT_LEN),
+    ];
+    for (alg, output_len) in algorithms {
+        assert_eq!(alg.output_len(), output_len);
+        assert!(alg.block_len() <= MAX_BLOCK_LEN);
+        assert!(alg.output_len() <= MAX_OUTPUT_LEN);
+        assert!(alg.chaining_len() <= MAX_CHAINING_LEN);
+
+        // From the upstream `Context` documentation.
+        let one_shot: Digest = digest(alg, b"hello, world");
+        let mut ctx = Context::new(alg);
+        ctx.update(b"hello");
+        ctx.update(b", ");
+        ctx.update(b"world");
+        assert_eq!(ctx.algorithm(), alg);
+        let multi_part: Digest = ctx.clone().finish();
+        assert_eq!(&one_shot.as_ref(), &multi_part.as_ref());
+        assert_eq!(multi_part.algorithm(), alg);
+        let _: String = format!("{:?}", multi_part);
+    }
+
+    // From the upstream `digest` documentation.
+    let expected_hex = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+    let expected: Vec<u8> = test::from_hex(expected_hex).unwrap();
+    let actual = digest(&SHA256, b"hello, world");
+    assert_eq!(&expected, &actual.as_ref());
+}
+
+#[test]
+fn algorithm_builder() {
+    const SHA256_IV: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    const SHA512_256_IV: [u64; 8] = [
+        0x22312194fc2bf72c,
+        0x9f555fa3c84c64c2,
+        0x2393b86b6f53b151,
+        0x963877195940eabd,
+        0x96283ee2a88effe3,
+        0xbe5e1e2553863992,
+        0x2b0199fc2c85b8aa,
+        0x0eb72ddc81c52ca2,
+    ];
+
+    fn leak(algorithm: digest::Algorithm) -> &'static digest::Algorithm {
+        Box::leak(Box::new(algorithm))
+    }
+
+    let sha256 = leak(digest::AlgorithmBuilder::sha256(SHA256_IV).build().unwrap());
+    let sha512_256 = leak(
+        digest::AlgorithmBuilder::sha512(SHA512_256_IV)
+            .output_len(32)
+            .build()
+            .unwrap(),
+    );
+    for (built, standard) in [(sha256, &digest::SHA256), (sha512_256, &digest::SHA512_256)] {
+        assert_eq!(built.output_len(), standard.output_len());
+        assert_eq!(built.block_len(), standard.block_len());
+        assert_eq!(built.chaining_len(), standard.chaining_len());
+        // A built algorithm is only equal to itself.
+        assert_eq!(built, built);
+        assert_ne!(built, standard);
+
+        for len in [0, 1, 55, 56, 64, 111, 112, 128, 1000] {
+            let data = vec![0x61u8; len];
+            assert_eq!(
+                digest::digest(built, &data).as_ref(),
+                digest::digest(standard, &data).as_ref()
+            );
+            let mut ctx = digest::Context::new(built);
+            ctx.update(&data);
+            assert_eq!(ctx.algorithm(), built);
+            assert_eq!(
+                ctx.finish().as_ref(),
+                digest::digest(standard, &data).as_ref()
+            );
+        }
+    }
+
+    // A different initial state gives a different function.
+    let mut iv = SHA256_IV;
+    iv[7] ^= 1;
+    let custom = leak(digest::AlgorithmBuilder::sha256(iv).build().unwrap());
+    assert_ne!(
+        digest::digest(custom, b"abc").as_ref(),
+        digest::digest(&digest::SHA256, b"abc").as_ref()
+    );
+
+    // Truncation to a shorter valid length.
+    let truncated = leak(
+        digest::AlgorithmBuilder::sha256(SHA256_IV)
+            .output_len(20)
+            .build()
+            .unwrap(),
+    );
+    assert_eq!(
+        digest::digest(truncated, b"abc").as_ref(),
+        &digest::digest(&digest::SHA256, b"abc").as_ref()[..20]
+    );
+
+    // Invalid output lengths.
+    assert!(digest::AlgorithmBuilder::sha256(SHA256_IV)
+        .output_len(48)
+        .build()
+        .is_err());
+    for len in [0, 16, 33, 65] {
+        assert!(digest::AlgorithmBuilder::sha512(SHA512_256_IV)
+            .output_len(len)
+            .build()
+            .is_err());
+    }
+}
+
+#[test]
+fn sha256_x8() {
+    let mut blocks = [[0u8; 64]; 8];
+    for (lane, block) in blocks.iter_mut().enumerate() {
+        for (i, b) in block.iter_mut().enumerate() {
+            *b = (i * 13 + lane * 101).to_le_bytes()[0];
+        }
+    }
+    // Lanes with identical and extreme contents.
+    blocks[6] = blocks[5];
+    blocks[7] = [0xff; 64];
+
+    let digests = digest::sha256_x8(&blocks);
+    for (actual, block) in digests.iter().zip(&blocks) {
+        assert_eq!(&actual[..], digest::digest(&digest::SHA256, block).as_ref());
+    }
+    assert_eq!(digests[5], digests[6]);
+}
+
+#[test]
+fn context_tiny_updates() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 7).to_le_bytes()[0]).collect();
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+    ] {
+        let expected = digest::digest(alg, &data);
+        let block_len = alg.block_len();
+        // Chunk sizes that start updates at and away from block boundaries.
+        for chunk_len in [1, 3, 7, 16, block_len - 1, block_len, block_len + 1] {
+            let mut ctx = digest::Context::new(alg);
+            data.chunks(chunk_len).for_each(|chunk| ctx.update(chunk));
+            assert_eq!(ctx.finish().as_ref(), expected.as_ref());
+        }
+
+        // Empty and tiny updates at block boundaries.
+        let mut ctx = digest::Context::new(alg);
+        ctx.update(&[]);
+        ctx.update(&data[..block_len]);
+        ctx.update(&[]);
+        ctx.update(&data[block_len..(block_len + 5)]);
+        ctx.update(&data[(block_len + 5)..]);
+        assert_eq!(ctx.finish().as_ref(), expected.as_ref());
+    }
+}
+
+#[test]
+fn context_seal_then_finish() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"hello");
+    assert!(!ctx.is_sealed());
+    ctx.seal();
+    assert!(ctx.is_sealed());
+    assert!(ctx.clone().is_sealed());
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, b"hello").as_ref()
+    );
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "sealed context")]
+fn context_update_after_seal_panics() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.seal();
+    ctx.update(b"too late");
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+fn context_update_after_seal_ignored() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"hello");
+    ctx.seal();
+    ctx.update(b"too late");
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, b"hello").as_ref()
+    );
+}
+
+#[test]
+fn context_pending_length_bits() {
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        let mut ctx = digest::Context::new(alg);
+        assert_eq!(ctx.pending_length_bits(), 0);
+        ctx.update(&[0u8; 100]);
+        assert_eq!(ctx.pending_length_bits(), 800);
+        ctx.update(&[0u8; 1]);
+        assert_eq!(ctx.pending_length_bits(), 808);
+
+        // It is the length encoded at the end of the final block.
+        let final_blocks = ctx.final_blocks();
+        let encoded = &final_blocks[(final_blocks.len() - 8)..];
+        assert_eq!(encoded, &808u64.to_be_bytes()[..]);
+    }
+}
+
+#[test]
+fn context_finish_with_len() {
+    let input = [0x5au8; 300];
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        for len in [0, 1, 128, 300] {
+            let mut ctx = digest::Context::new(alg);
+            input[..len].chunks(7).for_each(|chunk| ctx.update(chunk));
+            let (digest, hashed) = ctx.finish_with_len();
+            assert_eq!(hashed, u64::try_from(len).unwrap());
+            assert_eq!(digest.as_ref(), digest::digest(alg, &input[..len]).as_ref());
+        }
+    }
+}
+
+#[test]
+fn canonical_hasher_framing() {
+    use digest::{CanonicalHash, CanonicalHasher};
+
+    let fields = |fields: &[&[u8]]| {
+        let mut hasher = CanonicalHasher::new(&digest::SHA256);
+        fields.iter().for_each(|f| hasher.write_field(f));
+        hasher.finish()
+    };
+    assert_ne!(
+        fields(&[b"ab", b"c"]).as_ref(),
+        fields(&[b"a", b"bc"]).as_ref()
+    );
+    assert_eq!(
+        fields(&[b"ab"]).as_ref(),
+        digest::digest(&digest::SHA256, b"\0\0\0\0\0\0\0\x02ab").as_ref()
+    );
+
+    assert_eq!(
+        "ab".canonical_hash(&digest::SHA256).as_ref(),
+        fields(&[b"ab"]).as_ref()
+    );
+    assert_eq!(
+        0x0102u16.canonical_hash(&digest::SHA256).as_ref(),
+        fields(&[&[1, 2]]).as_ref()
+    );
+    assert_eq!(
+        true.canonical_hash(&digest::SHA256).as_ref(),
+        fields(&[&[1]]).as_ref()
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derive_canonical_hash() {
+    use digest::{CanonicalHash, CanonicalHasher};
+
+    #[derive(CanonicalHash)]
+    struct Record {
+        name: String,
+        id: u32,
+        #[hash(skip)]
+        cached: Option<digest::Digest>,
+        payload: Vec<u8>,
+    }
+
+    #[derive(CanonicalHash)]
+    struct Reordered {
+        id: u32,
+        name: String,
+        payload: Vec<u8>,
+    }
+
+    #[derive(CanonicalHash)]
+    struct Tuple<'a>(&'a str, u32, #[hash(skip)] u64);
+
+    let record = Record {
+        name: "alice".into(),
+        id: 7,
+        cached: None,
+        payload: vec![1, 2, 3],
+    };
+
+    // Fields are absorbed in declaration order, each length-framed.
+    let mut hasher = CanonicalHasher::new(&digest::SHA256);
+    hasher.write_field(b"alice");
+    hasher.write_field(&7u32.to_be_bytes());
+    hasher.write_field(&[1, 2, 3]);
+    let expected = hasher.finish();
+    assert_eq!(
+        record.canonical_hash(&digest::SHA256).as_ref(),
+        expected.as_ref()
+    );
+
+    // Skipped fields don't affect the digest.
+    let cached = Record {
+        cached: Some(expected),
+        ..record
+    };
+    assert!(cached.cached.is_some());
+    assert_eq!(
+        cached.canonical_hash(&digest::SHA256).as_ref(),
+        expected.as_ref()
+    );
+
+    // Field order does.
+    let reordered = Reordered {
+        id: 7,
+        name: "alice".into(),
+        payload: vec![1, 2, 3],
+    };
+    assert_ne!(
+        reordered.canonical_hash(&digest::SHA256).as_ref(),
+        expected.as_ref()
+    );
+
+    let tuple = Tuple("bob", 1, 99);
+    assert_eq!(tuple.2, 99);
+    let mut hasher = CanonicalHasher::new(&digest::SHA384);
+    hasher.write_field(b"bob");
+    hasher.write_field(&1u32.to_be_bytes());
+    assert_eq!(
+        tuple.canonical_hash(&digest::SHA384).as_ref(),
+        hasher.finish().as_ref()
+    );
+}
+
+#[test]
+fn profiling_context_histogram_merge() {
+    let shard_a = [0x5au8; 100];
+    let shard_b: Vec<u8> = (0..1000u32).map(|i| i.to_le_bytes()[0]).collect();
+
+    let mut a = digest::ProfilingContext::new(&digest::SHA256);
+    a.update(&shard_a);
+    let mut b = digest::ProfilingContext::new(&digest::SHA256);
+    b.update(&shard_b[..500]);
+    b.update(&shard_b[500..]);
+
+    let mut whole = digest::ProfilingContext::new(&digest::SHA256);
+    whole.update(&shard_a);
+    whole.update(&shard_b);
+
+    let mut merged = a.histogram();
+    merged.merge(&b.histogram());
+    assert_eq!(merged, whole.histogram());
+    assert_eq!(merged.total(), 1100);
+    assert_eq!(merged.counts()[0x5a], 100 + 4);
+
+    assert_eq!(
+        whole.finish().as_ref(),
+        digest::digest(&digest::SHA256, &[&shard_a[..], &shard_b].concat()).as_ref()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn byte_histogram_entropy_bits() {
+    let histogram = |data: &[u8]| {
+        let mut ctx = digest::ProfilingContext::new(&digest::SHA256);
+        ctx.update(data);
+        ctx.histogram()
+    };
+
+    assert_eq!(histogram(b"").entropy_bits(), 0.0);
+    assert_eq!(histogram(&[7; 100]).entropy_bits(), 0.0);
+    assert_eq!(histogram(b"abababab").entropy_bits(), 1.0);
+    let all: Vec<u8> = (0..=255).collect();
+    assert_eq!(histogram(&all).entropy_bits(), 8.0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn context_io_copy() {
+    let input: Vec<u8> = (0..(3 * 1024 * 1024 + 17))
+        .map(|i: u32| i.to_le_bytes()[0] ^ i.to_le_bytes()[2])
+        .collect();
+
+    for &alg in &[&digest::SHA256, &digest::SHA512] {
+        let mut ctx = digest::Context::new(alg);
+        let copied = std::io::copy(&mut std::io::Cursor::new(&input), &mut ctx).unwrap();
+        assert_eq!(copied, u64::try_from(input.len()).unwrap());
+        std::io::Write::flush(&mut ctx).unwrap();
+        assert_eq!(ctx.finish().as_ref(), digest::digest(alg, &input).as_ref());
+    }
+
+    // Writing to a sealed context fails instead of panicking.
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    std::io::Write::write_all(&mut ctx, b"abc").unwrap();
+    ctx.seal();
+    assert!(std::io::Write::write(&mut ctx, b"def").is_err());
+    assert!(std::io::Write::write_all(&mut ctx, b"def").is_err());
+    assert!(std::io::copy(&mut &b"def"[..], &mut ctx).is_err());
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, b"abc").as_ref()
+    );
+}
+
+#[test]
+fn context_reset() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA512,
+    ] {
+        let a = [0xa5u8; 300];
+        let b = b"message b";
+
+        let mut ctx = digest::Context::new(alg);
+        ctx.update(&a);
+        ctx.seal();
+        ctx.reset();
+        assert!(!ctx.is_sealed());
+        assert_eq!(ctx.algorithm(), alg);
+        ctx.update(b);
+
+        let mut fresh = digest::Context::new(alg);
+        fresh.update(b);
+        assert_eq!(ctx.finish().as_ref(), fresh.finish().as_ref());
+    }
+}
+
+#[test]
+fn digest_into_raw_from_raw() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA512_256,
+    ] {
+        let d = digest::digest(alg, b"merkle leaf");
+        let (value, len, id) = d.into_raw().unwrap();
+        assert_eq!(len, alg.output_len());
+        assert_eq!(&value[..len], d.as_ref());
+
+        let restored = digest::Digest::from_raw(value, len, id).unwrap();
+        assert_eq!(restored.as_ref(), d.as_ref());
+        assert_eq!(restored.algorithm(), alg);
+
+        assert!(digest::Digest::from_raw(value, len - 1, id).is_err());
+    }
+
+    let custom = Box::leak(Box::new(
+        digest::AlgorithmBuilder::sha256([1; 8]).build().unwrap(),
+    ));
+    assert_eq!(
+        digest::digest(custom, b"").into_raw(),
+        Err(digest::DigestError::CustomAlgorithm)
+    );
+}
+
+#[test]
+fn digest_hex_of() {
+    assert_eq!(
+        digest::hex_of(&digest::SHA256, b"x"),
+        "2d711642b726b04401627ca9fbac32f5c8530fb1903cc4db02258717921a4881"
+    );
+    assert_eq!(
+        digest::hex_of(&digest::SHA512_224, b"abc"),
+        digest::digest(&digest::SHA512_224, b"abc").to_hex()
+    );
+}
+
+#[test]
+fn digest_typed() {
+    struct Invoice;
+    struct Receipt;
+
+    let data = b"amount=100";
+    let invoice = digest::digest_typed::<Invoice>(&digest::SHA256, data);
+    let receipt = digest::digest_typed::<Receipt>(&digest::SHA256, data);
+    assert_ne!(invoice.as_ref(), receipt.as_ref());
+    assert_ne!(
+        invoice.as_ref(),
+        digest::digest(&digest::SHA256, data).as_ref()
+    );
+    assert_eq!(
+        invoice.as_ref(),
+        digest::digest_typed::<Invoice>(&digest::SHA256, data).as_ref()
+    );
+    assert_ne!(
+        digest::digest_typed::<str>(&digest::SHA256, data).as_ref(),
+        digest::digest_typed::<[u8]>(&digest::SHA256, data).as_ref()
+    );
+}
+
+#[test]
+fn context_unbuffer() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"abcde");
+    ctx.unbuffer(2).unwrap();
+    assert_eq!(
+        ctx.clone().finish().as_ref(),
+        digest::digest(&digest::SHA256, b"abc").as_ref()
+    );
+    ctx.update(b"XY");
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, b"abcXY").as_ref()
+    );
+
+    // Absorbed blocks can't be rewound.
+    let block_len = digest::SHA256.block_len();
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(&vec![0; block_len + 1]);
+    assert_eq!(ctx.unbuffer(2), Err(digest::CannotUnbuffer));
+    ctx.unbuffer(1).unwrap();
+    assert_eq!(ctx.unbuffer(1), Err(digest::CannotUnbuffer));
+    assert_eq!(
+        ctx.finish().as_ref(),
+        digest::digest(&digest::SHA256, &vec![0; block_len]).as_ref()
+    );
+
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(b"abc");
+    ctx.seal();
+    assert_eq!(ctx.unbuffer(1), Err(digest::CannotUnbuffer));
+}
+
+#[test]
+fn digest_eq() {
+    let a = digest::digest(&digest::SHA256, b"a");
+    assert_eq!(a, digest::digest(&digest::SHA256, b"a"));
+    assert_ne!(a, digest::digest(&digest::SHA256, b"b"));
+    assert!(a.verify_eq(a.as_ref()));
+    assert!(!a.verify_eq(digest::digest(&digest::SHA256, b"b").as_ref()));
+    assert!(!a.verify_eq(&a.as_ref()[..31]));
+    assert!(!a.verify_eq(b""));
+
+    // SHA-512/256 output is as long as SHA-256 output, and the builder can
+    // reproduce SHA-256 exactly; neither compares equal to SHA-256.
+    assert_ne!(a, digest::digest(&digest::SHA512_256, b"a"));
+    assert_ne!(
+        digest::digest(&digest::SHA384, b"a"),
+        digest::digest(&digest::SHA512, b"a")
+    );
+    let custom = Box::leak(Box::new(
+        digest::AlgorithmBuilder::sha256([
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ])
+        .build()
+        .unwrap(),
+    ));
+    let custom = digest::digest(custom, b"a");
+    assert_eq!(custom.as_ref(), a.as_ref());
+    assert_ne!(custom, a);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn digest_serde() {
+    for (alg, name) in [
+        (&digest::SHA1_FOR_LEGACY_USE_ONLY, "SHA1"),
+        (&digest::SHA256, "SHA256"),
+        (&digest::SHA512_224, "SHA512_224"),
+    ] {
+        let d = digest::digest(alg, b"abc");
+        let json = serde_json::to_string(&d).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"algorithm":"{}","value":"{}"}}"#, name, d.to_hex())
+        );
+        let restored: digest::Digest = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, d);
+    }
+
+    let value = digest::digest(&digest::SHA256, b"abc").to_hex();
+    let bad = [
+        // Truncated.
+        format!(r#"{{"algorithm":"SHA256","value":"{}"}}"#, &value[..62]),
+        format!(r#"{{"algorithm":"SHA256","value":"{}"}}"#, &value[..63]),
+        // The value of a different algorithm.
+        format!(r#"{{"algorithm":"SHA512","value":"{}"}}"#, value),
+        format!(r#"{{"algorithm":"SHA256","value":"{}x"}}"#, &value[..63]),
+        format!(r#"{{"algorithm":"MD5","value":"{}"}}"#, value),
+        format!(r#"{{"algorithm":"CUSTOM_SHA256","value":"{}"}}"#, value),
+        format!(r#"{{"value":"{}"}}"#, value),
+    ];
+    for json in bad {
+        assert!(
+            serde_json::from_str::<digest::Digest>(&json).is_err(),
+            "{}",
+            json
+        );
+    }
+}
+
+#[test]
+fn digest_as_map_key() {
+    use std::collections::{BTreeSet, HashMap};
+
+    let messages: [&[u8]; 4] = [b"", b"a", b"b", b"abc"];
+    let algs = [
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA512,
+    ];
+    let digests: Vec<_> = algs
+        .iter()
+        .flat_map(|&alg| messages.iter().map(move |m| digest::digest(alg, m)))
+        .collect();
+
+    let mut map = HashMap::new();
+    let mut set = BTreeSet::new();
+    for (i, d) in digests.iter().enumerate() {
+        assert_eq!(map.insert(*d, i), None);
+        assert!(set.insert(*d));
+    }
+    assert_eq!(map.len(), digests.len());
+    assert_eq!(set.len(), digests.len());
+    for (i, &alg) in algs.iter().enumerate() {
+        for (j, m) in messages.iter().enumerate() {
+            let d = digest::digest(alg, m);
+            assert_eq!(map[&d], i * messages.len() + j);
+            assert!(set.contains(&d));
+        }
+    }
+    assert!(!set.contains(&digest::digest(&digest::SHA256, b"c")));
+
+    // Ordered by algorithm id first, then by value.
+    let sorted: Vec<_> = set.into_iter().collect();
+    for pair in sorted.windows(2) {
+        let (a, b) = (pair[0].algorithm(), pair[1].algorithm());
+        assert!(
+            a.output_len() <= b.output_len(),
+            "{:?} before {:?}",
+            pair[0],
+            pair[1]
+        );
+        if a == b {
+            assert!(pair[0].as_ref() < pair[1].as_ref());
+        }
+    }
+
+    // Distinct custom algorithms are distinct keys even with equal values.
+    let iv = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let custom1 = Box::leak(Box::new(
+        digest::AlgorithmBuilder::sha256(iv).build().unwrap(),
+    ));
+    let custom2 = Box::leak(Box::new(
+        digest::AlgorithmBuilder::sha256(iv).build().unwrap(),
+    ));
+    let (c1, c2) = (digest::digest(custom1, b"a"), digest::digest(custom2, b"a"));
+    assert_ne!(c1.cmp(&c2), std::cmp::Ordering::Equal);
+    assert_eq!(c1.cmp(&c1), std::cmp::Ordering::Equal);
+    assert_eq!(BTreeSet::from([c1, c2, c1]).len(), 2);
+}
+
+#[test]
+fn digest_commitment() {
+    let rng = ring::rand::SystemRandom::new();
+    let (commitment, opening) = digest::commit(&digest::SHA256, b"my bid: 100", &rng).unwrap();
+    assert_eq!(commitment.as_ref().len(), digest::SHA256_OUTPUT_LEN);
+    assert!(digest::verify_commitment(
+        &digest::SHA256,
+        &commitment,
+        b"my bid: 100",
+        &opening
+    ));
+    assert!(!digest::verify_commitment(
+        &digest::SHA256,
+        &commitment,
+        b"my bid: 101",
+        &opening
+    ));
+    assert!(!digest::verify_commitment(
+        &digest::SHA512_256,
+        &commitment,
+        b"my bid: 100",
+        &opening
+    ));
+    let wrong_opening = digest::Opening::new([0; digest::COMMITMENT_SALT_LEN]);
+    assert!(!digest::verify_commitment(
+        &digest::SHA256,
+        &commitment,
+        b"my bid: 100",
+        &wrong_opening
+    ));
+
+    // The commitment is H(salt || data), and commitments to the same data
+    // use fresh salts.
+    let mut salted = opening.salt().to_vec();
+    salted.extend_from_slice(b"my bid: 100");
+    assert_eq!(
+        commitment,
+        digest::Commitment::from(digest::digest(&digest::SHA256, &salted))
+    );
+    let (again, _) = digest::commit(&digest::SHA256, b"my bid: 100", &rng).unwrap();
+    assert_ne!(commitment, again);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn tee_hashing_writer() {
+    use std::io::Write;
+
+    struct ShortWriter(Vec<u8>);
+
+    // Accepts at most 7 bytes per write.
+    impl Write for ShortWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(7);
+            self.0.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let algorithms = [&digest::SHA256, &digest::SHA512];
+    let input: Vec<u8> = (0..10_000u32).map(|i| i.to_le_bytes()[0]).collect();
+
+    let mut writer = digest::TeeHashingWriter::new(Vec::new(), &algorithms).unwrap();
+    std::io::copy(&mut &input[..], &mut writer).unwrap();
+    writer.flush().unwrap();
+    let (stored, digests) = writer.finish();
+    assert_eq!(stored, input);
+    assert_eq!(digests.len(), algorithms.len());
+    for (digest, alg) in digests.iter().zip(algorithms) {
+        assert_eq!(digest, &digest::digest(alg, &input));
+    }
+
+    let mut writer = digest::TeeHashingWriter::new(ShortWriter(Vec::new()), &algorithms).unwrap();
+    assert_eq!(writer.write(&input).unwrap(), 7);
+    writer.write_all(&input[7..]).unwrap();
+    assert_eq!(writer.get_ref().0.len(), input.len());
+    let (stored, digests) = writer.finish();
+    assert_eq!(stored.0, input);
+    assert_eq!(digests[1], digest::digest(&digest::SHA512, &input));
+
+    assert!(
+        digest::TeeHashingWriter::new(Vec::new(), &[&digest::SHA256, &digest::SHA256]).is_err()
+    );
+}
+
+#[test]
+fn context_debug_dump() {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(&[0u8; 64]);
+    ctx.update(b"abc");
+    let dump = ctx.debug_dump();
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "algorithm: SHA256",
+            "completed_bytes: 64",
+            "num_pending: 3",
+            lines[3],
+            "pending: 616263",
+            "sealed: false",
+        ]
+    );
+    let state: Vec<&str> = lines[3]
+        .strip_prefix("state: ")
+        .unwrap()
+        .split(' ')
+        .collect();
+    assert_eq!(state.len(), 8);
+    assert!(state.iter().all(|w| w.len() == 8));
+
+    let mut ctx = digest::Context::new(&digest::SHA512);
+    ctx.seal();
+    let dump = ctx.debug_dump();
+    assert!(dump.contains("algorithm: SHA512\n"));
+    assert!(dump.contains("completed_bytes: 0\n"));
+    assert!(dump.contains("state: 6a09e667f3bcc908 bb67ae8584caa73b "));
+    assert!(dump.contains("pending: \n"));
+    assert!(dump.contains("sealed: true\n"));
+}
+
+#[test]
+fn digest_from_hex() {
+    use digest::ParseDigestError;
+
+    const HELLO_WORLD: &str = "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b";
+    let expected = digest::digest(&digest::SHA256, b"hello, world");
+
+    let parsed = digest::Digest::from_hex(&digest::SHA256, HELLO_WORLD).unwrap();
+    assert_eq!(parsed, expected);
+    let parsed = digest::Digest::from_hex(&digest::SHA256, &HELLO_WORLD.to_uppercase()).unwrap();
+    assert_eq!(parsed, expected);
+    for &alg in &[&digest::SHA1_FOR_LEGACY_USE_ONLY, &digest::SHA512] {
+        let d = digest::digest(alg, b"round trip");
+        assert_eq!(digest::Digest::from_hex(alg, &d.to_hex()), Ok(d));
+    }
+
+    assert_eq!(
+        digest::Digest::from_hex(&digest::SHA256, &HELLO_WORLD[1..]),
+        Err(ParseDigestError::OddLength.into())
+    );
+    assert_eq!(
+        digest::Digest::from_hex(&digest::SHA256, &HELLO_WORLD[2..]),
+        Err(ParseDigestError::WrongLength {
+            expected: 64,
+            actual: 62
+        }
+        .into())
+    );
+    assert_eq!(
+        digest::Digest::from_hex(&digest::SHA512, HELLO_WORLD),
+        Err(ParseDigestError::WrongLength {
+            expected: 128,
+            actual: 64
+        }
+        .into())
+    );
+    let bad_char = HELLO_WORLD.replacen('e', "g", 1);
+    assert_eq!(
+        digest::Digest::from_hex(&digest::SHA256, &bad_char),
+        Err(ParseDigestError::BadChar.into())
+    );
+    assert_eq!(
+        digest::Digest::from_hex(&digest::SHA256, &HELLO_WORLD.replacen('0', " ", 1)),
+        Err(ParseDigestError::BadChar.into())
+    );
+}
+
+#[test]
+fn digest_display() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+        &digest::SHA512_224,
+    ] {
+        let digest = digest::digest(alg, b"hello, world");
+        let displayed = format!("{}", digest);
+        assert_eq!(displayed.len(), 2 * alg.output_len());
+        assert!(displayed
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)));
+        assert_eq!(displayed, digest.to_hex());
+        assert_eq!(format!("{:?}", digest), format!("{:?}:{}", alg, displayed));
+    }
+}
+
+#[test]
+fn context_midstate() {
+    for &alg in &[
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+        &digest::SHA512_256,
+    ] {
+        let block_len = alg.block_len();
+        let input: Vec<u8> = (0..(3 * block_len + 10))
+            .map(|i: usize| i.to_le_bytes()[0])
+            .collect();
+        let expected = digest::digest(alg, &input);
+
+        for blocks in [0, 1, 3] {
+            let split = blocks * block_len;
+            let mut ctx = digest::Context::new(alg);
+            ctx.update(&input[..split]);
+            let midstate = ctx.midstate().unwrap();
+            assert_eq!(midstate.as_ref().len(), alg.chaining_len());
+
+            let completed_bytes = u64::try_from(split).unwrap();
+            let mut restored =
+                digest::Context::from_midstate(alg, midstate.as_ref(), completed_bytes).unwrap();
+            restored.update(&input[split..]);
+            assert_eq!(restored.finish(), expected);
+        }
+
+        let mut ctx = digest::Context::new(alg);
+        ctx.update(&input[..(block_len + 1)]);
+        assert!(ctx.midstate().is_none());
+
+        let midstate = digest::Context::new(alg).midstate().unwrap();
+        assert!(digest::Context::from_midstate(alg, midstate.as_ref(), 1).is_err());
+        assert!(digest::Context::from_midstate(alg, &midstate.as_ref()[1..], 0).is_err());
+    }
+
+    // The chaining value of a new context is the algorithm's IV.
+    let midstate = digest::Context::new(&digest::SHA256).midstate().unwrap();
+    assert_eq!(&midstate.as_ref()[..4], &[0x6a, 0x09, 0xe6, 0x67]);
+}
+
+#[test]
+fn digest_into() {
+    let expected = digest::digest(&digest::SHA384, b"hello, world");
+
+    let mut out = [0u8; digest::SHA384_OUTPUT_LEN];
+    digest::digest_into(&digest::SHA384, b"hello, world", &mut out).unwrap();
+    assert_eq!(&out[..], expected.as_ref());
+
+    // Only the first `output_len()` bytes are written.
+    let mut out = [0xffu8; digest::MAX_OUTPUT_LEN];
+    digest::digest_into(&digest::SHA384, b"hello, world", &mut out).unwrap();
+    assert_eq!(&out[..digest::SHA384_OUTPUT_LEN], expected.as_ref());
+    assert!(out[digest::SHA384_OUTPUT_LEN..].iter().all(|&b| b == 0xff));
+
+    let mut out = [0u8; digest::SHA384_OUTPUT_LEN - 1];
+    assert_eq!(
+        digest::digest_into(&digest::SHA384, b"hello, world", &mut out),
+        Err(digest::BufferTooSmall.into())
+    );
+    assert!(out.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn tree_hash_structure() {
+    use digest::tree;
+
+    fn leaf(chunk: &[u8]) -> digest::Digest {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(&[tree::LEAF_PREFIX]);
+        ctx.update(chunk);
+        ctx.finish()
+    }
+    fn node(left: &digest::Digest, right: &digest::Digest) -> digest::Digest {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(&[tree::NODE_PREFIX]);
+        ctx.update(left.as_ref());
+        ctx.update(right.as_ref());
+        ctx.finish()
+    }
+
+    assert_eq!(tree::hash(&digest::SHA256, b"", 4).unwrap(), leaf(b""));
+    assert_eq!(
+        tree::hash(&digest::SHA256, b"abcd", 4).unwrap(),
+        leaf(b"abcd")
+    );
+    assert_eq!(
+        tree::hash(&digest::SHA256, b"abcdefgh", 4).unwrap(),
+        node(&leaf(b"abcd"), &leaf(b"efgh"))
+    );
+    // Five chunks: the left subtree is the first four.
+    assert_eq!(
+        tree::hash(&digest::SHA256, b"abcdefghi", 2).unwrap(),
+        node(
+            &node(
+                &node(&leaf(b"ab"), &leaf(b"cd")),
+                &node(&leaf(b"ef"), &leaf(b"gh"))
+            ),
+            &leaf(b"i")
+        )
+    );
+}
+
+#[test]
+fn tree_hash_deterministic() {
+    use digest::tree;
+
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    for chunk_len in [1, 63, 64, 1000, 4096, 10_000, 20_000] {
+        let root = tree::hash(&digest::SHA256, &data, chunk_len).unwrap();
+        assert_eq!(tree::hash(&digest::SHA256, &data, chunk_len).unwrap(), root);
+
+        #[cfg(feature = "rayon")]
+        for threads in [1, 2, 3, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            assert_eq!(
+                pool.install(|| tree::hash(&digest::SHA256, &data, chunk_len).unwrap()),
+                root
+            );
+        }
+    }
+    // The root depends on the chunk length.
+    assert_ne!(
+        tree::hash(&digest::SHA256, &data, 1000).unwrap(),
+        tree::hash(&digest::SHA256, &data, 10_000).unwrap()
+    );
+}
+
+#[test]
+fn tree_hash_zero_chunk_len() {
+    assert_eq!(
+        digest::tree::hash(&digest::SHA256, b"abc", 0),
+        Err(ring::error::Unspecified)
+    );
+}
+
+#[test]
+fn digest_batch_matches_digest() {
+    let data: Vec<u8> = (0..4096u32).map(|i| (i % 253) as u8).collect();
+    // Runs of eight 64-byte inputs, which SHA-256 hashes together, broken
+    // up by inputs of other lengths.
+    let mut inputs: Vec<&[u8]> = Vec::new();
+    for (i, len) in [0, 1, 55, 56, 63, 64, 65, 128, 1000].iter().enumerate() {
+        inputs.push(&data[i..][..*len]);
+    }
+    for run in [8, 7, 17] {
+        inputs.extend((0..run).map(|i| &data[i * 3..][..64]));
+        inputs.push(&data[..100]);
+    }
+    let runs_only: Vec<&[u8]> = (0..200).map(|i| &data[i..][..64]).collect();
+
+    for alg in [
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        &digest::SHA256,
+        &digest::SHA384,
+        &digest::SHA512,
+    ] {
+        for inputs in [&inputs[..], &runs_only, &[]] {
+            let batch = digest::digest_batch(alg, inputs);
+            assert_eq!(batch.len(), inputs.len());
+            for (d, input) in batch.iter().zip(inputs) {
+                assert_eq!(*d, digest::digest(alg, input));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn context_update_reader() {
+    use ring::rand::SecureRandom;
+    use std::io;
+
+    let mut data = vec![0u8; 100_000];
+    ring::rand::SystemRandom::new().fill(&mut data).unwrap();
+
+    for alg in [&digest::SHA256, &digest::SHA512] {
+        for len in [0, 1, 127, 128, 8192, 100_000] {
+            let mut ctx = digest::Context::new(alg);
+            let mut reader = io::Cursor::new(&data[..len]);
+            assert_eq!(ctx.update_reader(&mut reader).unwrap(), len as u64);
+            assert_eq!(ctx.finish(), digest::digest(alg, &data[..len]));
+        }
+    }
+
+    // Returns short reads, interrupts every other read, and then fails.
+    struct Flaky<'a> {
+        data: &'a [u8],
+        reads: usize,
+    }
+    impl io::Read for Flaky<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            if self.reads % 2 == 0 {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            if self.data.is_empty() {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            let n = buf.len().min(self.data.len()).min(1000);
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    let mut reader = Flaky {
+        data: &data[..5500],
+        reads: 0,
+    };
+    assert_eq!(
+        ctx.update_reader(&mut reader).unwrap_err().kind(),
+        io::ErrorKind::BrokenPipe
+    );
+    // Everything read before the error was hashed, and nothing else.
+    assert_eq!(ctx.finish(), digest::digest(&digest::SHA256, &data[..5500]));
+}
@@ -107,3 +107,60 @@ fn hmac_debug() {
     let ctx = hmac::Context::with_key(&key);
     assert_eq!("Context { algorithm: SHA256 }", format!("{:?}", &ctx));
 }
+
+#[cfg(feature = "serialize")]
+#[test]
+fn hmac_context_serialize() {
+    let input: Vec<u8> = (0..1000u32).map(|i| i.to_le_bytes()[0]).collect();
+    for algorithm in [
+        hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+        hmac::HMAC_SHA256,
+        hmac::HMAC_SHA384,
+        hmac::HMAC_SHA512,
+    ] {
+        let key = hmac::Key::new(algorithm, b"checkpointed key");
+        let expected = hmac::sign(&key, &input);
+        for split in [0, 1, 64, 500, 1000] {
+            let mut ctx = hmac::Context::with_key(&key);
+            ctx.update(&input[..split]);
+            let data = ctx.serialize();
+            drop(ctx);
+
+            let mut restored = hmac::Context::deserialize(data).unwrap();
+            restored.update(&input[split..]);
+            assert_eq!(restored.sign().as_ref(), expected.as_ref());
+        }
+    }
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, b"k");
+    let data = hmac::Context::with_key(&key).serialize();
+    let mut ctx = hmac::Context::with_key(&key);
+    ctx.update(b"more than the key");
+    let mut outer_not_key_only = data.clone();
+    outer_not_key_only.outer = ctx.serialize().inner;
+    assert!(hmac::Context::deserialize(outer_not_key_only).is_err());
+    let mut wrong_algorithm = data.clone();
+    wrong_algorithm.outer = hmac::Context::with_key(&hmac::Key::new(hmac::HMAC_SHA384, b"k"))
+        .serialize()
+        .outer;
+    assert!(hmac::Context::deserialize(wrong_algorithm).is_err());
+    let mut not_hmac = data;
+    not_hmac.inner.algorithm = digest::AlgorithmId::SHA512_256;
+    not_hmac.outer.algorithm = digest::AlgorithmId::SHA512_256;
+    assert!(hmac::Context::deserialize(not_hmac).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn hmac_context_serde() {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, b"checkpointed key");
+    let mut ctx = hmac::Context::with_key(&key);
+    ctx.update(b"hello, ");
+    let stored = serde_json::to_string(&ctx.serialize()).unwrap();
+    let mut restored = hmac::Context::deserialize(serde_json::from_str(&stored).unwrap()).unwrap();
+    restored.update(b"world");
+    assert_eq!(
+        restored.sign().as_ref(),
+        hmac::sign(&key, b"hello, world").as_ref()
+    );
+}